@@ -0,0 +1,42 @@
+use std::fs;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use logical_cpu_assembler::batch::{run_batch, BatchEntry};
+use logical_cpu_assembler::config::Config;
+
+const PROGRAMS: usize = 100;
+
+const SOURCE: &str = "\
+DATA R0 5
+DATA R1 3
+MARK loop
+ADD R0 R2
+NOT R1 R1
+JIF CZ done
+JMP loop
+MARK done
+END";
+
+fn batch(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join(format!("logical_cpu_assembler_batch_{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Unable to create the bench directory.");
+
+    let entries: Vec<BatchEntry> = (0..PROGRAMS)
+        .map(|i| {
+            let input = dir.join(format!("program_{}", i));
+            fs::write(&input, SOURCE).expect("Unable to write a bench program.");
+            BatchEntry { input, output: dir.join(format!("program_{}.ms", i)) }
+        })
+        .collect();
+
+    let config = Config::default();
+
+    c.bench_function("batch sequential", |b| b.iter(|| run_batch(&entries, &config, false)));
+    c.bench_function("batch parallel", |b| b.iter(|| run_batch(&entries, &config, true)));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+criterion_group!(benches, batch);
+criterion_main!(benches);
@@ -0,0 +1,49 @@
+use std::fs;
+
+use clap::ValueEnum;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rayon::prelude::*;
+
+use logical_cpu_assembler::assemble_str;
+use logical_cpu_assembler::assembler::Assembler;
+use logical_cpu_assembler::config::Config;
+use logical_cpu_assembler::output::{OutputFormat, OutputTarget};
+
+const TARGETS: usize = 10;
+
+//Repeated to make a program of a few hundred RAM cells.
+const LINES: &str = "\
+DATA R0 5
+ADD R0 R1
+NOT R1 R1
+";
+
+//The same program written as ten formats, one after another and then all at once as the
+//command line does unless --no-parallel-output is given.
+fn output(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join(format!("logical_cpu_assembler_output_{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Unable to create the bench directory.");
+
+    let source = LINES.repeat(40) + "END\n";
+    let program = assemble_str(&source, &Config::default()).expect("The bench program assembles.");
+
+    let targets: Vec<OutputTarget> = OutputFormat::value_variants().iter()
+        .take(TARGETS)
+        .enumerate()
+        .map(|(i, format)| OutputTarget { format: *format, path: dir.join(format!("program_{}.{}", i, format.extension())), coe_radix: None })
+        .collect();
+
+    let assembler = Assembler::default();
+
+    c.bench_function("output sequential", |b| b.iter(|| {
+        targets.iter().try_for_each(|target| assembler.write_output(&program, target)).expect("The outputs are written.")
+    }));
+    c.bench_function("output parallel", |b| b.iter(|| {
+        targets.par_iter().try_for_each(|target| assembler.write_output(&program, target)).expect("The outputs are written.")
+    }));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+criterion_group!(benches, output);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use logical_cpu_assembler::assembler::{parse_line, ParseContext};
+use logical_cpu_assembler::config::Config;
+
+//One line for each kind of instruction, directive and pseudo instruction.
+const LINES: [&str; 20] = [
+    "ADD R0 R1",
+    "SHR R1 R2",
+    "SHL R2 R3",
+    "NOT R3 R0",
+    "AND R0 R0",
+    "OR R1 R1",
+    "XOR R2 R2",
+    "ST R0 R1",
+    "LD R2 R3",
+    "DATA R0 200",
+    "JMPR R1",
+    "JMP start",
+    "JIF CAEZ start",
+    "JIFN C start",
+    "CLF",
+    "END",
+    "MARK start",
+    "# comment",
+    "XNOR R0 R1",
+    "BSET R0 3",
+];
+
+fn parse_lines(c: &mut Criterion) {
+    let config = Config::default();
+
+    for line in LINES {
+        c.bench_function(line, |b| {
+            b.iter(|| {
+                let mut ctx = ParseContext::new(&config);
+                parse_line(black_box(line), &mut ctx)
+            })
+        });
+    }
+}
+
+criterion_group!(benches, parse_lines);
+criterion_main!(benches);
@@ -0,0 +1,141 @@
+//Assembles a random program, disassembles the machine code, assembles that again and checks both
+//builds are the same. Run with cargo fuzz run fuzz_roundtrip.
+
+#![no_main]
+
+use std::collections::HashMap;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use logical_cpu_assembler::assemble_str;
+use logical_cpu_assembler::config::Config;
+use logical_cpu_assembler::disassembler::{disassemble, write_source};
+
+//Most statements in a program, keeps it inside the 256 RAM cells of the default config.
+const MAX_STATEMENTS: usize = 100;
+
+#[derive(Arbitrary, Debug)]
+enum Data {
+    Zero,
+    One,
+    Max,
+    Value(u8),
+}
+
+impl Data {
+    fn value(&self) -> u8 {
+        match self {
+            Data::Zero => 0,
+            Data::One => 1,
+            Data::Max => u8::MAX,
+            Data::Value(value) => *value,
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+enum Statement {
+    Mark,
+    Add(u8, u8),
+    Shr(u8, u8),
+    Shl(u8, u8),
+    Not(u8, u8),
+    And(u8, u8),
+    Or(u8, u8),
+    XOr(u8, u8),
+    Store(u8, u8),
+    Load(u8, u8),
+    Data(u8, Data),
+    JumpRegister(u8),
+    //The mark jumped to is picked from the marks of the program.
+    JumpAddress(u8),
+    JumpIf(u8, u8),
+    JumpIfNot(u8, u8),
+    ClearFlags,
+    End,
+}
+
+fn register(reg: u8) -> String {
+    format!("R{}", reg % 4)
+}
+
+//At least one flag, JIF without flags is not valid and neither is JIFN with all of them.
+fn flags(flags: u8, all_allowed: bool) -> String {
+    let mut bits = flags % 16;
+    if bits == 0 || (!all_allowed && bits == 15) {
+        bits = 1;
+    }
+
+    "CAEZ".chars()
+        .enumerate()
+        .filter(|(i, _)| bits & (8 >> i) != 0)
+        .map(|(_, flag)| flag)
+        .collect()
+}
+
+#[derive(Arbitrary, Debug)]
+struct Program {
+    statements: Vec<Statement>,
+}
+
+impl Program {
+    fn source(&self) -> String {
+        let statements = &self.statements[..self.statements.len().min(MAX_STATEMENTS)];
+        let num_marks = statements.iter().filter(|statement| matches!(statement, Statement::Mark)).count();
+
+        //Jumps need a mark to go to.
+        let mut source = String::from("MARK m0\n");
+        let mark = |index: u8| format!("m{}", index as usize % (num_marks + 1));
+
+        let mut next_mark = 1;
+        for statement in statements {
+            let line =
+                match statement {
+                    Statement::Mark => {
+                        next_mark += 1;
+                        format!("MARK m{}", next_mark - 1)
+                    }
+                    Statement::Add(a, b) => format!("ADD {} {}", register(*a), register(*b)),
+                    Statement::Shr(a, b) => format!("SHR {} {}", register(*a), register(*b)),
+                    Statement::Shl(a, b) => format!("SHL {} {}", register(*a), register(*b)),
+                    Statement::Not(a, b) => format!("NOT {} {}", register(*a), register(*b)),
+                    Statement::And(a, b) => format!("AND {} {}", register(*a), register(*b)),
+                    Statement::Or(a, b) => format!("OR {} {}", register(*a), register(*b)),
+                    Statement::XOr(a, b) => format!("XOR {} {}", register(*a), register(*b)),
+                    Statement::Store(a, b) => format!("ST {} {}", register(*a), register(*b)),
+                    Statement::Load(a, b) => format!("LD {} {}", register(*a), register(*b)),
+                    Statement::Data(reg, data) => format!("DATA {} {}", register(*reg), data.value()),
+                    Statement::JumpRegister(reg) => format!("JMPR {}", register(*reg)),
+                    Statement::JumpAddress(target) => format!("JMP {}", mark(*target)),
+                    Statement::JumpIf(tested, target) => format!("JIF {} {}", flags(*tested, true), mark(*target)),
+                    Statement::JumpIfNot(tested, target) => format!("JIFN {} {}", flags(*tested, false), mark(*target)),
+                    Statement::ClearFlags => "CLF".to_string(),
+                    Statement::End => "END".to_string(),
+                };
+
+            source.push_str(&line);
+            source.push('\n');
+        }
+
+        source
+    }
+}
+
+fuzz_target!(|program: Program| {
+    let config = Config::default();
+    let source = program.source();
+    let first = assemble_str(&source, &config).expect("Generated programs are valid.");
+
+    let symbols: HashMap<String, usize> = first.marks.clone().into_iter().collect();
+    let instructions = disassemble(&first.machine_code, Some(&symbols)).expect("Assembled programs disassemble.");
+
+    //Without the END the assembler added, which is added again.
+    let mut disassembled = Vec::new();
+    write_source(&instructions[..instructions.len() - 1], Some(&symbols), &mut disassembled).unwrap();
+    let disassembled = String::from_utf8(disassembled).unwrap();
+
+    let second = assemble_str(&disassembled, &config).expect("Disassembled programs assemble.");
+
+    assert_eq!(first.machine_code, second.machine_code, "Source:\n{}\nDisassembled:\n{}", source, disassembled);
+});
@@ -0,0 +1,57 @@
+//Assembles a program while the crate using it is being compiled.
+//
+//    const PROGRAM: [u8; 4] = asm! { DATA R2 42; ADD R0 R1 };
+//
+//Instructions are separated by ';'. Invalid assembly is reported as a compile error.
+
+use proc_macro::{TokenStream, TokenTree};
+
+use logical_cpu_assembler::assemble_str;
+use logical_cpu_assembler::config::Config;
+use logical_cpu_assembler::output::to_bytes;
+
+fn to_source(input: TokenStream) -> String {
+    let mut source = String::new();
+    for token in input {
+        match token {
+            TokenTree::Punct(p) if p.as_char() == ';' => source.push('\n'),
+            _ => {
+                source.push_str(&token.to_string());
+                source.push(' ');
+            }
+        }
+    }
+
+    source
+}
+
+/// Expands to the bytes of the program.
+///
+/// ```
+/// const PROGRAM: [u8; 2] = logical_cpu_assembler_macro::asm! { ADD R0 R1 };
+/// assert_eq!(PROGRAM, [0b10000001, 0b11001111]);
+/// ```
+///
+/// An instruction that does not exist is a compile error.
+///
+/// ```compile_fail
+/// const PROGRAM: [u8; 2] = logical_cpu_assembler_macro::asm! { MOVE R0 R1 };
+/// ```
+#[proc_macro]
+pub fn asm(input: TokenStream) -> TokenStream {
+    let source = to_source(input);
+
+    let expanded =
+        match assemble_str(&source, &Config::default()) {
+            Ok(program) => {
+                let bytes: Vec<String> = to_bytes(&program.machine_code).iter()
+                    .map(|b| format!("{:#04x}", b))
+                    .collect();
+
+                format!("{{ const PROGRAM: [u8; {}] = [{}]; PROGRAM }}", bytes.len(), bytes.join(", "))
+            }
+            Err(e) => format!("compile_error!({:?})", e.to_string()),
+        };
+
+    expanded.parse().expect("Generated code must be valid tokens.")
+}
@@ -0,0 +1,13 @@
+use logical_cpu_assembler::assemble_str;
+use logical_cpu_assembler::config::Config;
+use logical_cpu_assembler::output::to_bytes;
+use logical_cpu_assembler_macro::asm;
+
+const PROGRAM: [u8; 4] = asm! { DATA R2 42; ADD R0 R1 };
+
+#[test]
+fn asm_matches_assemble_str() {
+    let program = assemble_str("DATA R2 42\nADD R0 R1\n", &Config::default()).expect("The program assembles.");
+
+    assert_eq!(PROGRAM.to_vec(), to_bytes(&program.machine_code));
+}
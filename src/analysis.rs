@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+
+use crate::assembler::ParseContext;
+use crate::ast::{AstVisitor, Program, SpannedDirective, SpannedInstruction, SpannedMark, VisitResult};
+use crate::instructions::{FlagSet, Instructions, Register};
+use crate::mark_name::split_offset;
+use crate::warning::Warning;
+
+//Without a final END or jump, execution runs into whatever follows the last instruction.
+#[derive(Default)]
+struct TerminalCheck {
+    last: Option<(usize, Instructions)>,
+}
+
+impl AstVisitor for TerminalCheck {
+    fn visit_instruction(&mut self, instr: &SpannedInstruction) -> VisitResult {
+        self.last = Some((instr.line, instr.instruction.clone()));
+        VisitResult::Continue
+    }
+}
+
+impl TerminalCheck {
+    fn finish(self, warnings: &mut Vec<Warning>) {
+        if let Some((line, instruction)) = self.last {
+            match instruction {
+                Instructions::End
+                | Instructions::JumpAddress { .. }
+                | Instructions::JumpRegister { .. }
+                //Instructions from a loaded instruction set can not be checked.
+                | Instructions::Custom { .. } => {}
+                _ => warnings.push(Warning::NoTerminalInstruction { line }),
+            }
+        }
+    }
+}
+
+//Registers an instruction reads and writes, the basis of the register liveness checks.
+//Instructions from a loaded instruction set are not known to use any register.
+pub fn register_usage(instruction: &Instructions) -> (Vec<Register>, Vec<Register>) {
+    match instruction {
+        Instructions::Add { reg_a, reg_b }
+        | Instructions::And { reg_a, reg_b }
+        | Instructions::Or { reg_a, reg_b }
+        | Instructions::XOr { reg_a, reg_b } => {
+            (vec![reg_a.clone(), reg_b.clone()], vec![reg_b.clone()])
+        }
+        Instructions::Shr { reg_a, reg_b }
+        | Instructions::Shl { reg_a, reg_b }
+        | Instructions::Not { reg_a, reg_b }
+        | Instructions::Load { reg_a, reg_b } => {
+            (vec![reg_a.clone()], vec![reg_b.clone()])
+        }
+        Instructions::Store { reg_a, reg_b } => (vec![reg_a.clone(), reg_b.clone()], vec![]),
+        Instructions::Data { reg, .. } | Instructions::PcRelative { reg, .. } => (vec![], vec![reg.clone()]),
+        Instructions::JumpRegister { reg } | Instructions::Debug { reg, .. } => (vec![reg.clone()], vec![]),
+        Instructions::JumpAddress { .. }
+        | Instructions::JumpIf { .. }
+        | Instructions::JumpIfNot { .. }
+        | Instructions::ClearFlags
+        | Instructions::Nop { .. }
+        | Instructions::Breakpoint { .. }
+        | Instructions::End
+        | Instructions::Custom { .. } => (vec![], vec![]),
+    }
+}
+
+pub fn sets_flags(instruction: &Instructions) -> bool {
+    matches!(
+        instruction,
+        Instructions::Add { .. }
+        | Instructions::Shr { .. }
+        | Instructions::Shl { .. }
+        | Instructions::Not { .. }
+        | Instructions::And { .. }
+        | Instructions::Or { .. }
+        | Instructions::XOr { .. }
+        //The opcode of a NOP and instructions from a loaded instruction set may set any flag.
+        | Instructions::Nop { .. }
+        | Instructions::Custom { .. }
+    )
+}
+
+//A CLF is pointless when the flags are still clear from the previous one. A jump can land on a
+//MARK between the two, so nothing is known about the flags after one.
+#[derive(Default)]
+struct RedundantClearFlagsCheck {
+    previous_clf_line: Option<usize>,
+    warnings: Vec<Warning>,
+}
+
+impl AstVisitor for RedundantClearFlagsCheck {
+    fn visit_instruction(&mut self, instr: &SpannedInstruction) -> VisitResult {
+        match instr.instruction {
+            Instructions::ClearFlags => {
+                if let Some(first_clf_line) = self.previous_clf_line {
+                    self.warnings.push(Warning::RedundantClearFlags { first_clf_line, second_clf_line: instr.line });
+                }
+                self.previous_clf_line = Some(instr.line);
+            }
+            _ if sets_flags(&instr.instruction) => {
+                self.previous_clf_line = None;
+            }
+            _ => {}
+        }
+
+        VisitResult::Continue
+    }
+
+    fn visit_mark(&mut self, _mark: &SpannedMark) -> VisitResult {
+        self.previous_clf_line = None;
+        VisitResult::Continue
+    }
+}
+
+//Tracks which flags are known to be zero since the last CLF, a JIF that only tests those
+//flags can never jump.
+#[derive(Default)]
+struct AlwaysFalseConditionalCheck {
+    known_zero: FlagSet,
+    warnings: Vec<Warning>,
+}
+
+impl AstVisitor for AlwaysFalseConditionalCheck {
+    fn visit_instruction(&mut self, instr: &SpannedInstruction) -> VisitResult {
+        let tested =
+            match &instr.instruction {
+                Instructions::ClearFlags => {
+                    self.known_zero = FlagSet::ALL;
+                    None
+                }
+                Instructions::JumpIf { carry, a_larger, equal, zero, .. } => {
+                    Some(FlagSet { carry: *carry, a_larger: *a_larger, equal: *equal, zero: *zero })
+                }
+                //Tests the flags that are not given.
+                Instructions::JumpIfNot { carry, a_larger, equal, zero, .. } => {
+                    Some(FlagSet { carry: !*carry, a_larger: !*a_larger, equal: !*equal, zero: !*zero })
+                }
+                instruction if sets_flags(instruction) => {
+                    self.known_zero = FlagSet::default();
+                    None
+                }
+                _ => None,
+            };
+
+        if let Some(tested) = tested {
+            if !self.known_zero.is_empty() && tested.is_subset(&self.known_zero) {
+                self.warnings.push(Warning::AlwaysFalseConditional { line: instr.line, known_zero_flags: self.known_zero });
+            }
+        }
+
+        VisitResult::Continue
+    }
+
+    fn visit_mark(&mut self, _mark: &SpannedMark) -> VisitResult {
+        self.known_zero = FlagSet::default();
+        VisitResult::Continue
+    }
+}
+
+//A mark that nothing jumps to is usually a typo in the jump meant for it.
+#[derive(Default)]
+struct UnusedMarkCheck {
+    marks: Vec<(String, usize)>,
+    used: HashSet<String>,
+}
+
+impl AstVisitor for UnusedMarkCheck {
+    fn visit_instruction(&mut self, instr: &SpannedInstruction) -> VisitResult {
+        if let Some(mark) = instr.instruction.referenced_mark() {
+            self.used.insert(split_offset(mark).0.to_string());
+        }
+        VisitResult::Continue
+    }
+
+    fn visit_mark(&mut self, mark: &SpannedMark) -> VisitResult {
+        self.marks.push((mark.name.clone(), mark.line));
+        VisitResult::Continue
+    }
+
+    fn visit_directive(&mut self, directive: &SpannedDirective) -> VisitResult {
+        match (directive.name.as_str(), directive.arguments.as_slice()) {
+            (".entry", [mark]) => {
+                self.used.insert(mark.clone());
+            }
+            //Any word of the expression may be a mark, words of the message are not marks.
+            (".assert", arguments) => {
+                let expression = arguments.join(" ");
+                let expression = expression.split('"').next().unwrap_or_default();
+                self.used.extend(expression.split(|c: char| !c.is_ascii_alphanumeric() && c != '_').map(|word| word.to_string()));
+            }
+            _ => {}
+        }
+        VisitResult::Continue
+    }
+}
+
+impl UnusedMarkCheck {
+    fn finish(self, warnings: &mut Vec<Warning>) {
+        for (mark, line) in self.marks {
+            if !self.used.contains(&mark) {
+                warnings.push(Warning::UnusedMark { line, mark });
+            }
+        }
+    }
+}
+
+//Runs the checks done after parsing and adds them to the warnings found while parsing, warnings
+//turned off in the config or by a comment are left out.
+pub fn analyze(program: &Program, ctx: &ParseContext) -> Vec<Warning> {
+    let mut warnings = ctx.warnings.clone();
+
+    let mut terminal = TerminalCheck::default();
+    terminal.walk_program(program);
+    terminal.finish(&mut warnings);
+
+    let mut redundant_clear_flags = RedundantClearFlagsCheck::default();
+    redundant_clear_flags.walk_program(program);
+    warnings.extend(redundant_clear_flags.warnings);
+
+    let mut always_false_conditionals = AlwaysFalseConditionalCheck::default();
+    always_false_conditionals.walk_program(program);
+    warnings.extend(always_false_conditionals.warnings);
+
+    let mut unused_marks = UnusedMarkCheck::default();
+    unused_marks.walk_program(program);
+    unused_marks.finish(&mut warnings);
+
+    warnings.retain(|warning| ctx.reports(warning));
+    warnings
+}
@@ -0,0 +1,1289 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use log::{debug, trace};
+use sha2::{Digest, Sha256};
+use tracing::instrument;
+
+use crate::analysis::analyze;
+use crate::ast::{parse_program, parse_statements, Program};
+use crate::checksum::append_crc16;
+use crate::config::{Config, ConfigError};
+use crate::control_flow::{self, ControlBlock};
+use crate::directive::{self, parse_assert, Assertion};
+use crate::disassembler::validate_encoding;
+use crate::error::AssemblerError;
+use crate::feature::FeatureStack;
+use crate::file_system::{FileSystem, RealFileSystem};
+use crate::instructions::{data_truncated, CustomOperand, Instructions, Operand, Register, INSTRUCTION_SET};
+use crate::isa::Isa;
+use crate::macros::{Macro, STD_LIBRARY};
+use crate::mark_name::{mark_address, split_offset, MarkName, INTERNAL_MARK_PREFIX};
+use crate::output::{to_bytes, OutputTarget};
+use crate::pseudo::{self, PSEUDO_INSTRUCTIONS};
+use crate::timing::TimingModel;
+use crate::warning::{Suppression, Warning};
+
+//An instruction along with where it came from in the source and where it is placed in RAM.
+#[derive(Clone, Debug)]
+pub struct AssembledInstruction {
+    pub line: usize,
+    pub address: usize,
+    //Number of RAM cells the instruction takes up.
+    pub size: usize,
+    pub instruction: Instructions,
+}
+
+//Information about the program set by directives, kept for debug outputs.
+#[derive(Clone, Debug, Default)]
+pub struct ProgramMetadata {
+    //Clock speed of the CPU in Hz, set by .frequency.
+    pub frequency: Option<u64>,
+    //Other names for registers defined with .alias.
+    pub register_aliases: IndexMap<String, Register>,
+    //Name of each .module and the line it is on.
+    pub modules: Vec<(String, usize)>,
+}
+
+pub struct AssembledProgram {
+    //One binary string per RAM cell.
+    pub machine_code: Vec<String>,
+    //RAM address of each MARK.
+    pub marks: IndexMap<String, usize>,
+    //Source line each MARK is defined on.
+    pub mark_lines: IndexMap<String, usize>,
+    pub instructions: Vec<AssembledInstruction>,
+    pub warnings: Vec<Warning>,
+    //Most RAM cells the program may use, from --max-size or .budget.
+    pub budget: Option<usize>,
+    pub address_bits: usize,
+    pub word_bits: usize,
+    //SHA-256 hash of the source the program was assembled from.
+    pub source_hash: [u8; 32],
+    pub metadata: ProgramMetadata,
+    //Address execution starts at, set with .entry.
+    pub entry_point: usize,
+    //Cycles taken by each instruction, from --timing-model.
+    pub timing_model: TimingModel,
+    //Address the program is loaded at, from --base-address.
+    pub base_address: usize,
+}
+
+impl AssembledProgram {
+    //Number of instructions in the machine code. Found from the RAM cells that start an
+    //instruction so the END added by the assembler is counted too.
+    pub fn instruction_count(&self) -> usize {
+        self.machine_code.len() - self.instructions.iter().map(|assembled| assembled.size - 1).sum::<usize>()
+    }
+
+    pub fn breakpoint_addresses(&self) -> Vec<usize> {
+        self.instructions.iter()
+            .filter(|assembled| matches!(assembled.instruction, Instructions::Breakpoint { .. }))
+            .map(|assembled| assembled.address)
+            .collect()
+    }
+
+    //Cycles taken to run every instruction once, with the cycles of each from the timing model.
+    pub fn estimated_cycles(&self) -> usize {
+        //The END added by the assembler is not one of the instructions.
+        let end = self.instruction_count() - self.instructions.len();
+
+        self.instructions.iter()
+            .map(|assembled| self.timing_model.cycles(assembled.instruction.mnemonic()))
+            .sum::<usize>()
+            + end * self.timing_model.cycles("END")
+    }
+
+    //Time taken by estimated_cycles in microseconds, when the frequency is known.
+    pub fn estimated_time_us(&self) -> Option<f64> {
+        let frequency = self.metadata.frequency?;
+        Some(self.estimated_cycles() as f64 * 1_000_000.0 / frequency as f64)
+    }
+
+    //The program as size bytes placed where it is loaded, such as for initializing a ROM. The RAM
+    //cells below the --base-address are set to fill_before and bytes after the last RAM cell of
+    //the program to fill_after. Cells wider than 8 bits take up multiple bytes each, as in
+    //to_bytes. A program longer than size is cut off.
+    pub fn to_memory_image(&self, size: usize, fill_before: u8, fill_after: u8) -> Vec<u8> {
+        let bytes_per_cell = self.word_bits.div_ceil(8).max(1);
+
+        let mut image = vec![fill_before; self.base_address * bytes_per_cell];
+        image.extend(to_bytes(&self.machine_code));
+        image.resize(size, fill_after);
+        image
+    }
+}
+
+//Distance from a PCREL to its mark past which it gives a warning.
+pub const PCREL_WARN_OFFSET: i64 = 64;
+
+//Grammar of the assembly language, kept in step with parse_line by hand.
+const GRAMMAR_EBNF: &str = r##"program      = { line , newline } ;
+line         = [ statement | comment ] ;
+comment      = ( "#" | ? a string set with --comment-chars ? ) , { any character } ;
+statement    = mark | directive | macro | instruction ;
+
+mark         = "MARK" , name ;
+directive    = ".alias" , name , register
+             | ".assert" , expression , [ '"' , { any character except '"' } , '"' ]
+             | ".budget" , number
+             | ".breakpoint"
+             | ".checkpoint" , number
+             | ".entry" , name
+             | ".feature" , name
+             | ".endfeature"
+             | ".flagset" , name , flags
+             | ( ".if" | ".while" ) , ( flags | name ) , [ name ]
+             | ( ".endif" | ".endwhile" ) , [ name ]
+             | ".frequency" , number
+             | ".include" , "<std>"
+             | ".mnemonic" , name , name
+             | ".module" , name ;
+macro        = ".macro" , name , { name } , newline , { line , newline } , ".endm"
+             | name , { non whitespace character , { non whitespace character } } ;
+
+instruction  = alu | memory | data | jump | pseudo
+             | "CLF" | "END" | "NOP" | "DBG" , register ;
+alu          = ( "ADD" | "SHR" | "SHL" | "NOT" | "AND" | "OR" | "XOR" ) , register , register ;
+memory       = ( "ST" | "LD" ) , register , register ;
+data         = "DATA" , register , number ;
+jump         = "JMPR" , register
+             | "JMP" , target
+             | ( "JIF" | "JIFN" ) , ( flags | name ) , target ;
+target       = name , [ ( "+" | "-" ) , number ] ;
+pseudo       = ( "XNOR" | "NAND" | "NOR" | "ROL" | "ROR" | "SAR" ) , register , register
+             | ( "BT" | "BSET" | "BCLR" ) , register , number
+             | "PCREL" , register , name ;
+
+expression   = [ "-" | "!" ] , operand , { operator , [ "-" | "!" ] , operand } ;
+operand      = number | "0x" , hex digit , { hex digit } | "0b" , bit , { bit } | name | "(" , expression , ")" ;
+operator     = "||" | "&&" | "==" | "!=" | "<" | "<=" | ">" | ">=" | "+" | "-" | "*" | "/" | "%" ;
+
+register     = "R0" | "R1" | "R2" | "R3" ;
+flags        = flag , { flag } ;
+flag         = "C" | "A" | "E" | "Z" ;
+name         = non whitespace character , { non whitespace character } ;
+number       = digit , { digit } ;
+digit        = "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" ;
+"##;
+
+//Reads sources and writes outputs through its file system, assembling with its config.
+pub struct Assembler {
+    file_system: Arc<dyn FileSystem>,
+    config: Config,
+}
+
+impl Default for Assembler {
+    fn default() -> Self {
+        Assembler::new(Arc::new(RealFileSystem), Config::default()).expect("The default config is valid.")
+    }
+}
+
+impl Assembler {
+    //Fails with every problem of the config, so none of them is found part way through assembling.
+    pub fn new(file_system: Arc<dyn FileSystem>, config: Config) -> Result<Self, Vec<ConfigError>> {
+        config.validate()?;
+
+        Ok(Assembler { file_system, config })
+    }
+
+    pub fn assemble_file(&self, path: &Path) -> Result<AssembledProgram, AssemblerError> {
+        let content = self.file_system.read_to_string(path)
+            .map_err(|error| AssemblerError::Io { path: path.to_path_buf(), error })?;
+
+        assemble_str(&content, &self.config)
+    }
+
+    //The whole output is written at once, so a failed write never leaves part of it behind.
+    pub fn write_output(&self, program: &AssembledProgram, target: &OutputTarget) -> Result<(), AssemblerError> {
+        let mut data = Vec::new();
+        target.writer().write(program, &mut data)
+            .and_then(|_| self.file_system.write_all(&target.path, &data))
+            .map_err(|error| AssemblerError::Io { path: target.path.clone(), error })
+    }
+
+    //Only describes the built in instructions, not an instruction set loaded with --isa. Words
+    //are separated by any whitespace.
+    pub fn grammar_ebnf() -> &'static str {
+        GRAMMAR_EBNF
+    }
+
+    //Parses the source with the default config into statements without encoding them.
+    pub fn parse(source: &str) -> Result<Program, Vec<AssemblerError>> {
+        parse_program(source, &Config::default())
+    }
+}
+
+//State carried from one line to the next while parsing.
+#[derive(Clone)]
+pub struct ParseContext<'a> {
+    pub config: &'a Config,
+    //Kept in the order the marks are defined so output listing them is reproducible.
+    pub marks_to_machine_code: IndexMap<String, i32>,
+    //Source line each MARK is defined on.
+    pub mark_lines: IndexMap<String, usize>,
+    pub real_line_number: usize,
+    pub machine_code_line_number: i32,
+    //Set by the .budget directive.
+    pub budget: Option<usize>,
+    //Warnings found while parsing, such as a failed .checkpoint.
+    pub warnings: Vec<Warning>,
+    //Flag combinations named with the .flagset directive.
+    pub flagsets: HashMap<String, (bool, bool, bool, bool)>,
+    pub metadata: ProgramMetadata,
+    //Number of marks made by fresh_mark and uses of macros so far.
+    pub generated_marks: usize,
+    //Macros defined with .macro.
+    pub macros: HashMap<String, Macro>,
+    //Macro whose body is being read, until its .endm.
+    pub macro_definition: Option<(String, Macro)>,
+    //Names of the libraries already included, each is only included once.
+    pub includes: Vec<String>,
+    //Other names for instructions defined with .mnemonic, to the instruction they stand for.
+    pub mnemonic_aliases: HashMap<String, String>,
+    //Warnings turned off by # assembler: allow(...) comments.
+    pub suppressions: Vec<Suppression>,
+    //Number of .breakpoint directives so far.
+    pub breakpoints: usize,
+    //Mark set with .entry and the line it was set on.
+    pub entry: Option<(String, usize)>,
+    //Checked once every mark has an address.
+    pub assertions: Vec<Assertion>,
+    //.if and .while blocks that are still open, the innermost last.
+    pub control_blocks: Vec<ControlBlock>,
+    //Set by .module, marks without a module belong to it.
+    pub module: Option<String>,
+    //.feature blocks that are still open.
+    pub features: FeatureStack,
+}
+
+impl<'a> ParseContext<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        ParseContext {
+            config,
+            marks_to_machine_code: IndexMap::new(),
+            mark_lines: IndexMap::new(),
+            real_line_number: 0,
+            machine_code_line_number: -1,
+            budget: None,
+            warnings: Vec::new(),
+            flagsets: HashMap::new(),
+            metadata: ProgramMetadata::default(),
+            generated_marks: 0,
+            macros: HashMap::new(),
+            macro_definition: None,
+            includes: Vec::new(),
+            mnemonic_aliases: HashMap::new(),
+            suppressions: Vec::new(),
+            breakpoints: 0,
+            entry: None,
+            assertions: Vec::new(),
+            control_blocks: Vec::new(),
+            module: None,
+            features: FeatureStack::default(),
+        }
+    }
+
+    //A number no other generated mark or use of a macro has.
+    pub fn next_mark_id(&mut self) -> usize {
+        self.generated_marks += 1;
+        self.generated_marks
+    }
+
+    //Whether the warning is not turned off in the config or by a comment.
+    pub fn reports(&self, warning: &Warning) -> bool {
+        !self.config.disabled_warnings.contains(&warning.kind())
+            && !self.suppressions.iter().any(|suppression| suppression.covers(warning))
+    }
+
+    //Reads a register operand, written as its name or an alias defined with .alias.
+    pub fn register(&self, word: &str) -> Result<Register, AssemblerError> {
+        match self.metadata.register_aliases.get(word) {
+            Some(reg) => Ok(reg.clone()),
+            None => Register::reg_from_instr(word, self.real_line_number),
+        }
+    }
+
+    //Defines a mark made by a pseudo instruction rather than a MARK line.
+    pub fn define_mark(&mut self, mark: &str, address: i32) {
+        self.marks_to_machine_code.insert(mark.to_string(), address);
+        self.mark_lines.insert(mark.to_string(), self.real_line_number);
+    }
+}
+
+//A name for a mark made by the assembler, such as __internal_rol_carry_3, that no MARK of the
+//program can clash with.
+pub fn fresh_mark(prefix: &str, ctx: &mut ParseContext) -> String {
+    format!("{}{}_{}", INTERNAL_MARK_PREFIX, prefix, ctx.next_mark_id())
+}
+
+//Parses the flags tested by JIF, such as CA for carry or a larger.
+pub fn parse_flags(flags: &str, real_line_number: usize) -> Result<(bool, bool, bool, bool), AssemblerError> {
+    let mut carry = false;
+    let mut a_larger = false;
+    let mut equal = false;
+    let mut zero = false;
+
+    for c in flags.chars() {
+        match c {
+            'C' => {
+                carry = true;
+            }
+            'A' => {
+                a_larger = true;
+            }
+            'E' => {
+                equal = true;
+            }
+            'Z' => {
+                zero = true;
+            }
+            _ => return Err(AssemblerError::InvalidJumpFlag { line: real_line_number, flag: c })
+        }
+    }
+
+    Ok((carry, a_larger, equal, zero))
+}
+
+fn is_flag_string(word: &str) -> bool {
+    word.chars().all(|c| "CAEZ".contains(c))
+}
+
+//Parses the flags of a JIF, either written out or as a name defined with .flagset.
+pub(crate) fn resolve_flags(word: &str, ctx: &ParseContext) -> Result<(bool, bool, bool, bool), AssemblerError> {
+    if is_flag_string(word) {
+        return parse_flags(word, ctx.real_line_number);
+    }
+
+    ctx.flagsets.get(word).copied().ok_or_else(||
+        AssemblerError::UnknownFlagset { line: ctx.real_line_number, name: word.to_string() }
+    )
+}
+
+//DATA values wider than a RAM cell lose their high bits, an error in strict mode and a warning
+//otherwise. word is the value as written, a slice of line.
+fn check_data(data: usize, word: &str, line: &str, ctx: &mut ParseContext) -> Result<(), AssemblerError> {
+    let word_bits = ctx.config.word_bits;
+    let truncated = data_truncated(data, word_bits);
+    if truncated == data {
+        return Ok(());
+    }
+
+    let column = word.as_ptr() as usize - line.as_ptr() as usize + 1;
+    let line = ctx.real_line_number;
+
+    if ctx.config.strict {
+        return Err(AssemblerError::DataOutOfRange { line, column, data, truncated, word_bits });
+    }
+
+    ctx.warnings.push(Warning::DataTruncated { line, column, data, truncated, word_bits });
+    Ok(())
+}
+
+//Parses a single line of source into the instructions it produces, pseudo instructions produce
+//several and lines such as MARK produce none.
+#[instrument(skip(ctx))]
+pub fn parse_line(line: &str, ctx: &mut ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    ctx.real_line_number += 1;
+    let instructions = parse_statement(line, ctx)?;
+
+    //The last address is checked after each line, which includes every line of a macro or library
+    //used on it. The END second_pass adds after the program needs the last RAM cell.
+    let max_num_ram_cells = usize::pow(2, ctx.config.address_bits as u32);
+    let num_program_cells = max_num_ram_cells - usize::from(ctx.config.isa.is_none());
+    if ctx.machine_code_line_number >= num_program_cells as i32 {
+        return Err(AssemblerError::AddressSpaceExhausted { at_line: ctx.real_line_number });
+    }
+
+    Ok(instructions)
+}
+
+//Parses a line without moving on to the next line number, so the lines of a macro or an
+//included library belong to the line that used them.
+fn parse_statement(line: &str, ctx: &mut ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    let real_line_number = ctx.real_line_number;
+
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+
+    if !words.is_empty() {
+        trace!("[TOKENS] line={} words={:?}", real_line_number, words);
+    }
+
+    //The body of a macro is kept as it is until the macro is used.
+    if let Some((_, definition)) = &mut ctx.macro_definition {
+        if words.first() == Some(&".endm") {
+            if let Some((name, definition)) = ctx.macro_definition.take() {
+                ctx.macros.insert(name, definition);
+            }
+        } else {
+            definition.body.push(line.to_string());
+        }
+
+        return Ok(Vec::new());
+    }
+
+    //Empty line.
+    if words.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if words[0] == ".feature" {
+        if words.len() != 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let enabled = ctx.config.features.iter().any(|feature| feature == words[1]);
+        ctx.features.push(words[1], real_line_number, enabled);
+
+        return Ok(Vec::new());
+    }
+
+    if words[0] == ".endfeature" {
+        if words.len() != 1 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        if ctx.features.pop().is_none() {
+            return Err(AssemblerError::UnmatchedBlockEnd { line: real_line_number, directive: words[0].to_string() });
+        }
+
+        return Ok(Vec::new());
+    }
+
+    //Inside a .feature block that is left out.
+    if !ctx.features.is_active() {
+        return Ok(Vec::new());
+    }
+
+    //Comment, which may turn off warnings for the lines around it.
+    if let Some(comment) = ctx.config.comment(line) {
+        ctx.suppressions.extend(Suppression::parse(comment, real_line_number));
+        return Ok(Vec::new());
+    }
+
+    //Another name for an instruction, defined with .mnemonic.
+    let alias = ctx.mnemonic_aliases.get(words[0]).cloned();
+    if let Some(instruction) = &alias {
+        words[0] = instruction;
+    }
+
+    if words[0] == ".alias" {
+        if words.len() != 3 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let (alias, reg) = (words[1], words[2]);
+        if Register::reg_from_instr(alias, real_line_number).is_ok() {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let reg = Register::reg_from_instr(reg, real_line_number)?;
+        ctx.metadata.register_aliases.insert(alias.to_string(), reg);
+
+        return Ok(Vec::new());
+    }
+
+    if words[0] == ".mnemonic" {
+        if words.len() != 3 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let is_instruction = |name: &str| match &ctx.config.isa {
+            Some(isa) => isa.instructions.iter().any(|instruction| instruction.mnemonic == name),
+            None => INSTRUCTION_SET.iter().any(|info| info.mnemonic == name)
+                || PSEUDO_INSTRUCTIONS.iter().any(|(mnemonic, _)| *mnemonic == name),
+        };
+
+        let (alias, instruction) = (words[1], words[2]);
+        let alias_taken = is_instruction(alias)
+            || alias == "MARK"
+            || alias.starts_with('.')
+            || ctx.config.comment(alias).is_some()
+            || ctx.macros.contains_key(alias)
+            || ctx.mnemonic_aliases.contains_key(alias);
+
+        if alias_taken || !is_instruction(instruction) {
+            return Err(AssemblerError::InvalidMnemonicAlias {
+                line: real_line_number,
+                alias: alias.to_string(),
+                instruction: instruction.to_string(),
+            });
+        }
+
+        ctx.mnemonic_aliases.insert(alias.to_string(), instruction.to_string());
+
+        return Ok(Vec::new());
+    }
+
+    //Marked for a jump point.
+    if words[0] == "MARK" {
+        if words.len() != 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let mark_name = MarkName::parse(words[1])?;
+        if mark_name.is_internal() {
+            return Err(AssemblerError::ReservedMarkName { line: real_line_number, name: words[1].to_string() });
+        }
+
+        let mark_variable = mark_name.in_module(ctx.module.as_deref()).to_string();
+
+        ctx.marks_to_machine_code.insert(mark_variable.clone(), ctx.machine_code_line_number + 1);
+        ctx.mark_lines.insert(mark_variable.clone(), real_line_number);
+
+        debug!("[MARK] line={} name={} address={}", real_line_number, mark_variable, ctx.machine_code_line_number + 1);
+
+        return Ok(Vec::new());
+    }
+
+    //Marks after it belong to the module until the next .module.
+    if words[0] == ".module" {
+        if words.len() != 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let module = MarkName::parse(words[1])?;
+        if module.module.is_some() {
+            return Err(AssemblerError::InvalidMarkName { name: words[1].to_string() });
+        }
+
+        ctx.metadata.modules.push((module.name.clone(), real_line_number));
+        ctx.module = Some(module.name);
+
+        return Ok(Vec::new());
+    }
+
+    //Starts the definition of a macro, its parameters follow the name.
+    if words[0] == ".macro" {
+        if words.len() < 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let name = words[1];
+        let is_instruction = INSTRUCTION_SET.iter().any(|info| info.mnemonic == name)
+            || PSEUDO_INSTRUCTIONS.iter().any(|(mnemonic, _)| *mnemonic == name);
+        if is_instruction || ctx.macros.contains_key(name) {
+            return Err(AssemblerError::MacroAlreadyDefined { line: real_line_number, name: name.to_string() });
+        }
+
+        let params = words[2..].iter().map(|param| param.to_string()).collect();
+        ctx.macro_definition = Some((name.to_string(), Macro { params, body: Vec::new(), line: real_line_number }));
+
+        return Ok(Vec::new());
+    }
+
+    //Parses a library built into the assembler as if it was written here.
+    if words[0] == ".include" {
+        if words.len() != 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let library =
+            match words[1] {
+                "<std>" => STD_LIBRARY,
+                name => return Err(AssemblerError::UnknownInclude { line: real_line_number, name: name.to_string() }),
+            };
+
+        if ctx.includes.iter().any(|included| included == words[1]) {
+            return Ok(Vec::new());
+        }
+        ctx.includes.push(words[1].to_string());
+
+        let mut instructions = Vec::new();
+        //The library is written with # comments whatever --comment-chars is set to.
+        for library_line in library.lines().filter(|library_line| !library_line.trim_start().starts_with('#')) {
+            instructions.extend(parse_statement(library_line, ctx)?);
+        }
+
+        return Ok(instructions);
+    }
+
+    if let Some(definition) = ctx.macros.get(words[0]).cloned() {
+        let args = &words[1..];
+        if args.len() != definition.params.len() {
+            return Err(AssemblerError::MacroArguments {
+                line: real_line_number,
+                name: words[0].to_string(),
+                expected: definition.params.len(),
+                found: args.len(),
+            });
+        }
+
+        let id = ctx.next_mark_id();
+        let mut instructions = Vec::new();
+        for body_line in definition.expand(args, id, &ctx.config.scratch_reg) {
+            instructions.extend(parse_statement(&body_line, ctx)?);
+        }
+
+        return Ok(instructions);
+    }
+
+    //Size budget for the program.
+    if words[0] == ".budget" {
+        if words.len() != 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let budget = words[1].parse::<usize>().map_err(|_|
+            AssemblerError::InvalidData { line: real_line_number, data: words[1].to_string() }
+        )?;
+
+        ctx.budget = Some(budget);
+
+        return Ok(Vec::new());
+    }
+
+    //Where execution starts, a JMP to it is added at address 0 once all marks are known.
+    if words[0] == ".entry" {
+        if words.len() != 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        if ctx.entry.is_some() {
+            return Err(AssemblerError::EntryAlreadyDefined { line: real_line_number });
+        }
+
+        ctx.entry = Some((words[1].to_string(), real_line_number));
+
+        return Ok(Vec::new());
+    }
+
+    //Checked once every mark has an address.
+    if words[0] == ".assert" {
+        let arguments = line.trim_start().strip_prefix(".assert").unwrap_or_default();
+        ctx.assertions.push(parse_assert(arguments, real_line_number)?);
+
+        return Ok(Vec::new());
+    }
+
+    //Clock speed of the CPU.
+    if words[0] == ".frequency" {
+        if words.len() != 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let frequency = words[1].parse::<u64>().ok().filter(|frequency| *frequency > 0).ok_or_else(||
+            AssemblerError::InvalidData { line: real_line_number, data: words[1].to_string() }
+        )?;
+
+        ctx.metadata.frequency = Some(frequency);
+
+        return Ok(Vec::new());
+    }
+
+    //Names a combination of flags for JIF.
+    if words[0] == ".flagset" {
+        //A name made of flag letters would be read as the flags themselves.
+        if words.len() != 3 || is_flag_string(words[1]) {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let flags = parse_flags(words[2], real_line_number)?;
+        ctx.flagsets.insert(words[1].to_string(), flags);
+
+        return Ok(Vec::new());
+    }
+
+    //Warns when fewer RAM cells than expected are left at this point.
+    if words[0] == ".checkpoint" {
+        if words.len() != 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let required = words[1].parse::<usize>().map_err(|_|
+            AssemblerError::InvalidData { line: real_line_number, data: words[1].to_string() }
+        )?;
+
+        let max_num_ram_cells = usize::pow(2, ctx.config.address_bits as u32);
+        let remaining = max_num_ram_cells.saturating_sub((ctx.machine_code_line_number + 1) as usize);
+
+        if remaining < required {
+            ctx.warnings.push(Warning::CheckpointNotMet { line: real_line_number, remaining, required });
+        }
+
+        return Ok(Vec::new());
+    }
+
+    //A NOP in release builds, which are not run by a debugger.
+    if words[0] == ".breakpoint" {
+        if words.len() != 1 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        ctx.machine_code_line_number += 1;
+
+        if ctx.config.release {
+            return Ok(vec![Instructions::Nop { opcode: ctx.config.nop_opcode }]);
+        }
+
+        ctx.breakpoints += 1;
+        if ctx.breakpoints == ctx.config.max_breakpoints + 1 {
+            ctx.warnings.push(Warning::TooManyBreakpoints { line: real_line_number, maximum: ctx.config.max_breakpoints });
+        }
+
+        return Ok(vec![Instructions::Breakpoint { opcode: ctx.config.breakpoint_opcode }]);
+    }
+
+    //Left out of release builds entirely, so they run exactly as they would without it.
+    if words[0] == "DBG" {
+        if words.len() != 2 {
+            return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+        }
+
+        let reg = ctx.register(words[1])?;
+
+        if ctx.config.release {
+            return Ok(Vec::new());
+        }
+
+        ctx.machine_code_line_number += 1;
+
+        return Ok(vec![Instructions::Debug { opcode: ctx.config.debug_opcode, reg }]);
+    }
+
+    if let Some(isa) = &ctx.config.isa {
+        let instruction = parse_custom(line, &words, isa, ctx)?;
+
+        trace!("[PARSE] line={} instruction={}", real_line_number, trace_fields(&instruction));
+
+        return Ok(vec![instruction]);
+    }
+
+    if let Some(instructions) = control_flow::expand(&words, ctx)? {
+        return Ok(instructions);
+    }
+
+    let instructions =
+        match pseudo::expand(&words, ctx)? {
+            Some(instructions) => instructions,
+            None => vec![parse_instruction(line, &words, ctx)?],
+        };
+
+    for instruction in &instructions {
+        if let Some(mark) = instruction.referenced_mark() {
+            MarkName::parse(split_offset(mark).0)?;
+        }
+
+        trace!("[PARSE] line={} instruction={}", real_line_number, trace_fields(instruction));
+    }
+
+    Ok(instructions)
+}
+
+//Parses a single built in instruction.
+fn parse_instruction(line: &str, words: &[&str], ctx: &mut ParseContext) -> Result<Instructions, AssemblerError> {
+    let real_line_number = ctx.real_line_number;
+
+    let instruction = match words[0] {
+        //Values that use at least two registers.
+        "ADD" | "SHR" | "SHL" | "NOT" | "AND" | "OR" | "XOR" | "ST" | "LD" => {
+            if words.len() != 3 {
+                return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+            }
+
+            let reg_a = ctx.register(words[1])?;
+
+            let reg_b = ctx.register(words[2])?;
+
+            ctx.machine_code_line_number += 1;
+
+            match words[0] {
+                "ADD" => {
+                    Instructions::Add { reg_a, reg_b }
+                }
+                "SHR" => {
+                    Instructions::Shr { reg_a, reg_b }
+                }
+                "SHL" => {
+                    Instructions::Shl { reg_a, reg_b }
+                }
+                "NOT" => {
+                    Instructions::Not { reg_a, reg_b }
+                }
+                "AND" => {
+                    Instructions::And { reg_a, reg_b }
+                }
+                "OR" => {
+                    Instructions::Or { reg_a, reg_b }
+                }
+                "XOR" => {
+                    Instructions::XOr { reg_a, reg_b }
+                }
+                "ST" => {
+                    Instructions::Store { reg_a, reg_b }
+                }
+                "LD" => {
+                    Instructions::Load { reg_a, reg_b }
+                }
+                _ => return Err(AssemblerError::UnknownInstruction { line: real_line_number, instruction: words[0].to_string() })
+            }
+        }
+        "DATA" => {
+            if words.len() != 3 {
+                return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+            }
+
+            let reg = ctx.register(words[1])?;
+
+            let data: usize = words[2].parse().map_err(|_|
+                AssemblerError::InvalidData { line: real_line_number, data: words[2].to_string() }
+            )?;
+
+            check_data(data, words[2], line, ctx)?;
+
+            ctx.machine_code_line_number += 2;
+
+            Instructions::Data { reg, data }
+        }
+        "JMPR" => {
+            if words.len() != 2 {
+                return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+            }
+
+            let reg = ctx.register(words[1])?;
+
+            ctx.machine_code_line_number += 1;
+
+            Instructions::JumpRegister { reg }
+        }
+        "JMP" => {
+            if words.len() != 2 {
+                return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+            }
+
+            let mark_variable = words[1];
+
+            ctx.machine_code_line_number += 2;
+
+            Instructions::JumpAddress { mark: mark_variable.to_string() }
+        }
+        "JIF" => {
+            if words.len() != 3 {
+                return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+            }
+
+            let (carry, a_larger, equal, zero) = resolve_flags(words[1], ctx)?;
+
+            let mark_variable = words[2];
+
+            ctx.machine_code_line_number += 2;
+
+            Instructions::JumpIf { carry, a_larger, equal, zero, mark: mark_variable.to_string() }
+        }
+        "JIFN" => {
+            if words.len() != 3 {
+                return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+            }
+
+            let (carry, a_larger, equal, zero) = resolve_flags(words[1], ctx)?;
+
+            //The complement of every flag is no flags, which JIF can not encode.
+            if carry && a_larger && equal && zero {
+                return Err(AssemblerError::JifnNeverJumps { line: real_line_number });
+            }
+
+            let mark_variable = words[2];
+
+            ctx.machine_code_line_number += 2;
+
+            Instructions::JumpIfNot { carry, a_larger, equal, zero, mark: mark_variable.to_string() }
+        }
+        "CLF" => {
+            ctx.machine_code_line_number += 1;
+
+            Instructions::ClearFlags
+        }
+        "END" => {
+            ctx.machine_code_line_number += 1;
+
+            Instructions::End
+        }
+        "NOP" => {
+            if words.len() != 1 {
+                return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+            }
+
+            ctx.machine_code_line_number += 1;
+
+            Instructions::Nop { opcode: ctx.config.nop_opcode }
+        }
+        _ => return Err(AssemblerError::UnknownInstruction { line: real_line_number, instruction: words[0].to_string() })
+    };
+
+    Ok(instruction)
+}
+
+//Parses an instruction using the instruction set loaded with --isa.
+fn parse_custom(line: &str, words: &[&str], isa: &Isa, ctx: &mut ParseContext) -> Result<Instructions, AssemblerError> {
+    let real_line_number = ctx.real_line_number;
+
+    let definition = isa.find(words[0]).ok_or_else(||
+        AssemblerError::UnknownInstruction { line: real_line_number, instruction: words[0].to_string() }
+    )?;
+
+    if words.len() != definition.operands.len() + 1 {
+        return Err(AssemblerError::InvalidFormatting { line: real_line_number });
+    }
+
+    let mut operands = Vec::new();
+    let mut num_cells = 1;
+    for (operand, word) in definition.operands.iter().zip(&words[1..]) {
+        let operand =
+            match operand {
+                Operand::Reg => CustomOperand::Reg(ctx.register(word)?),
+                Operand::Flags => {
+                    let (carry, a_larger, equal, zero) = resolve_flags(word, ctx)?;
+                    CustomOperand::Flags { carry, a_larger, equal, zero }
+                }
+                Operand::Imm => {
+                    let data: usize = word.parse().map_err(|_|
+                        AssemblerError::InvalidData { line: real_line_number, data: word.to_string() }
+                    )?;
+
+                    check_data(data, word, line, ctx)?;
+
+                    num_cells += 1;
+                    CustomOperand::Imm(data)
+                }
+                Operand::Mark => {
+                    num_cells += 1;
+                    CustomOperand::Mark(word.to_string())
+                }
+            };
+
+        operands.push(operand);
+    }
+
+    ctx.machine_code_line_number += num_cells;
+
+    Ok(Instructions::Custom { mnemonic: definition.mnemonic.clone(), opcode: definition.opcode.clone(), operands })
+}
+
+//Describes the operands of an instruction for --trace.
+fn trace_fields(instruction: &Instructions) -> String {
+    let fields =
+        match instruction {
+            Instructions::Add { reg_a, reg_b }
+            | Instructions::Shr { reg_a, reg_b }
+            | Instructions::Shl { reg_a, reg_b }
+            | Instructions::Not { reg_a, reg_b }
+            | Instructions::And { reg_a, reg_b }
+            | Instructions::Or { reg_a, reg_b }
+            | Instructions::XOr { reg_a, reg_b }
+            | Instructions::Store { reg_a, reg_b }
+            | Instructions::Load { reg_a, reg_b } => {
+                format!(" reg_a={} reg_b={}", reg_a, reg_b)
+            }
+            Instructions::Data { reg, data } => format!(" reg={} data={}", reg, data),
+            Instructions::JumpRegister { reg } => format!(" reg={}", reg),
+            Instructions::Debug { opcode, reg } => format!(" opcode={:08b} reg={}", opcode, reg),
+            Instructions::JumpAddress { mark } => format!(" mark={}", mark),
+            Instructions::PcRelative { reg, mark } => format!(" reg={} mark={}", reg, mark),
+            Instructions::JumpIf { carry, a_larger, equal, zero, mark }
+            | Instructions::JumpIfNot { carry, a_larger, equal, zero, mark } => {
+                format!(" carry={} a_larger={} equal={} zero={} mark={}", carry, a_larger, equal, zero, mark)
+            }
+            Instructions::ClearFlags | Instructions::End => String::new(),
+            Instructions::Nop { opcode } | Instructions::Breakpoint { opcode } => format!(" opcode={:08b}", opcode),
+            Instructions::Custom { operands, .. } => {
+                operands.iter().map(|operand| format!(" {}", operand)).collect()
+            }
+        };
+
+    format!("{}{}", instruction.mnemonic(), fields)
+}
+
+//Parses every line of the source, recording the marks in ctx. Each instruction is returned
+//with the line it came from.
+#[instrument(skip_all)]
+pub fn first_pass(content: &str, ctx: &mut ParseContext) -> Result<Vec<(usize, Instructions)>, AssemblerError> {
+    parse_statements(content, ctx)
+        .map(|program| program.instructions())
+        .map_err(|mut errors| errors.remove(0))
+}
+
+//Inserts config.delay_slot_fill after every branch that is followed by another branch or by
+//nothing, moving the marks after it along.
+fn fill_delay_slots(instructions: &mut Vec<(usize, Instructions)>, ctx: &mut ParseContext) {
+    let fill =
+        match &ctx.config.delay_slot_fill {
+            Some(fill) => fill.clone(),
+            None => return,
+        };
+
+    let fill_size = fill.size(ctx.config.word_bits) as i32;
+
+    let mut address = 0;
+    let mut index = 0;
+    while index < instructions.len() {
+        let (line, instruction) = &instructions[index];
+        address += instruction.size(ctx.config.word_bits) as i32;
+
+        let needs_fill = instruction.is_branch()
+            && instructions.get(index + 1).is_none_or(|(_, next)| next.is_branch());
+
+        if needs_fill {
+            let line = *line;
+            for mark_address in ctx.marks_to_machine_code.values_mut() {
+                if *mark_address >= address {
+                    *mark_address += fill_size;
+                }
+            }
+
+            instructions.insert(index + 1, (line, fill.clone()));
+            ctx.machine_code_line_number += fill_size;
+            address += fill_size;
+            index += 1;
+        }
+
+        index += 1;
+    }
+}
+
+//Starts the program with a JMP to the .entry mark when it is not already at address 0, moving
+//every mark after it. Returns the address execution starts at.
+fn add_entry_jump(instructions: &mut Vec<(usize, Instructions)>, ctx: &mut ParseContext) -> usize {
+    let (mark, line) =
+        match &ctx.entry {
+            Some(entry) => entry.clone(),
+            None => return 0,
+        };
+
+    //parse_statements has already failed when the mark does not exist.
+    let address = ctx.marks_to_machine_code.get(&mark).copied().unwrap_or(0);
+    if address == 0 {
+        return 0;
+    }
+
+    let jump = Instructions::JumpAddress { mark };
+    let jump_size = jump.size(ctx.config.word_bits) as i32;
+    for mark_address in ctx.marks_to_machine_code.values_mut() {
+        *mark_address += jump_size;
+    }
+    ctx.machine_code_line_number += jump_size;
+    instructions.insert(0, (line, jump));
+
+    (address + jump_size) as usize
+}
+
+//Replaces every PCREL with a DATA of the distance from it to its mark, now that every address
+//is known.
+fn resolve_pc_relative(instructions: &mut [(usize, Instructions)], ctx: &ParseContext, warnings: &mut Vec<Warning>) -> Result<(), AssemblerError> {
+    let mut address = 0;
+    for (line, instruction) in instructions.iter_mut() {
+        let size = instruction.size(ctx.config.word_bits);
+
+        if let Instructions::PcRelative { reg, mark } = instruction {
+            let mark_address = mark_address(mark, &ctx.marks_to_machine_code).ok_or_else(||
+                AssemblerError::MarkNotFound { mark: split_offset(mark).0.to_string() }
+            )?;
+            let offset = mark_address - address as i64;
+
+            if i8::try_from(offset).is_err() {
+                return Err(AssemblerError::PcRelOutOfRange { line: *line, mark: mark.clone(), offset });
+            }
+
+            let warning = Warning::PcRelOffsetLarge { line: *line, mark: mark.clone(), offset };
+            if offset.abs() > PCREL_WARN_OFFSET && ctx.reports(&warning) {
+                warnings.push(warning);
+            }
+
+            trace!("[RESOLVE] mark={} offset={}", mark, offset);
+            //Negative distances are written in two's complement.
+            *instruction = Instructions::Data { reg: reg.clone(), data: data_truncated(offset as usize, ctx.config.word_bits) };
+        }
+
+        address += size;
+    }
+
+    Ok(())
+}
+
+#[instrument(skip(marks_to_machine_code, config))]
+pub fn resolve_mark(mark: &str, marks_to_machine_code: &IndexMap<String, i32>, config: &Config) -> Result<String, AssemblerError> {
+    let machine_line = mark_address(mark, marks_to_machine_code).ok_or_else(||
+        AssemblerError::MarkNotFound { mark: split_offset(mark).0.to_string() }
+    )?;
+
+    let max_num_ram_cells = usize::pow(2, config.address_bits as u32);
+    if !(0..max_num_ram_cells as i64).contains(&machine_line) {
+        return Err(AssemblerError::MarkOffsetOutOfRange { mark: mark.to_string(), address: machine_line, maximum: max_num_ram_cells });
+    }
+
+    let binary_input_number = format!("{:0width$b}", machine_line, width = config.address_bits);
+
+    trace!("[RESOLVE] mark={} address={}", mark, machine_line);
+
+    Ok(binary_input_number)
+}
+
+//Encodes an instruction along with the address of the mark it jumps to, if any.
+#[instrument(skip(marks_to_machine_code, config))]
+pub fn encode_instruction(instruction: Instructions, marks_to_machine_code: &IndexMap<String, i32>, config: &Config) -> Result<String, AssemblerError> {
+    let mark = instruction.jump_mark()
+        .map(|mark| resolve_mark(mark, marks_to_machine_code, config))
+        .transpose()?;
+
+    let mut binary_string = Instructions::binary(
+        instruction, config.word_bits,
+    );
+
+    if let Some(mark) = mark {
+        binary_string.push('\n');
+        binary_string.push_str(&mark);
+    }
+
+    Ok(binary_string)
+}
+
+//Encodes every instruction now that all marks are known.
+#[instrument(skip_all)]
+pub fn second_pass(instructions: &[(usize, Instructions)], ctx: &ParseContext) -> Result<Vec<String>, AssemblerError> {
+    let mut final_build: Vec<String> = Vec::new();
+    let mut address = 0;
+    for (_, instruction) in instructions {
+        let mnemonic = instruction.mnemonic();
+        let binary_string = encode_instruction(instruction.clone(), &ctx.marks_to_machine_code, ctx.config)?;
+
+        debug!("[ENCODE] {} → {:?} @ address={}", mnemonic, binary_string, address);
+
+        address += binary_string.lines().count();
+        final_build.push(binary_string);
+    }
+
+    //A loaded instruction set has no built in END to finish with.
+    if ctx.config.isa.is_none() {
+        final_build.push(
+            Instructions::binary(
+                Instructions::End, ctx.config.word_bits,
+            )
+        );
+    }
+
+    Ok(final_build)
+}
+
+//Adds the base address to the mark address after each jump, for a program loaded somewhere other
+//than address 0. Addresses past the end of RAM wrap around.
+fn relocate(final_build: &mut [String], instructions: &[(usize, Instructions)], config: &Config) {
+    if config.base_address == 0 {
+        return;
+    }
+
+    let max_num_ram_cells = usize::pow(2, config.address_bits as u32);
+    for (binary_string, (_, instruction)) in final_build.iter_mut().zip(instructions) {
+        if instruction.jump_mark().is_none() {
+            continue;
+        }
+
+        let (encoding, address) = binary_string.rsplit_once('\n').expect("The address of the mark is the last RAM cell of a jump.");
+        let address = usize::from_str_radix(address, 2).expect("Machine code must be a binary string.");
+        let relocated = (address + config.base_address) % max_num_ram_cells;
+
+        trace!("[RELOCATE] address={} relocated={}", address, relocated);
+
+        *binary_string = format!("{}\n{:0width$b}", encoding, relocated, width = config.address_bits);
+    }
+}
+
+pub fn source_hash(content: &str) -> [u8; 32] {
+    Sha256::digest(content).into()
+}
+
+pub fn assemble_str(content: &str, config: &Config) -> Result<AssembledProgram, AssemblerError> {
+    //The address bits and word bits are used as shift and pow amounts below, so they are checked
+    //before anything else even though the command line has already done so.
+    config.validate().map_err(|errors|
+        AssemblerError::InvalidConfig { reasons: errors.iter().map(|error| error.to_string()).collect() }
+    )?;
+
+    let max_num_ram_cells = usize::pow(2, config.address_bits as u32);
+
+    let mut ctx = ParseContext::new(config);
+    //Only the first error is returned.
+    let program = parse_statements(content, &mut ctx).map_err(|mut errors| errors.remove(0))?;
+    //Warnings are about the source as written, not the instructions added to it.
+    let mut warnings = analyze(&program, &ctx);
+    let mut instructions = program.instructions();
+    let entry_point = add_entry_jump(&mut instructions, &mut ctx);
+    fill_delay_slots(&mut instructions, &mut ctx);
+    resolve_pc_relative(&mut instructions, &ctx, &mut warnings)?;
+    for assertion in &ctx.assertions {
+        directive::assert(assertion, &ctx.marks_to_machine_code)?;
+    }
+    let mut final_build = second_pass(&instructions, &ctx)?;
+    relocate(&mut final_build, &instructions, config);
+
+    //Catches instructions that are encoded wrong while working on the assembler.
+    if cfg!(debug_assertions) && config.isa.is_none() && config.word_bits == 8 {
+        let encoded: Vec<Instructions> = instructions.iter().map(|(_, instruction)| instruction.clone()).collect();
+        let mismatches = validate_encoding(&encoded, &final_build);
+        let messages: Vec<String> = mismatches.iter().map(|mismatch| mismatch.to_string()).collect();
+        debug_assert!(mismatches.is_empty(), "{}", messages.join(" "));
+    }
+
+    if ctx.machine_code_line_number > max_num_ram_cells as i32 {
+        return Err(AssemblerError::TooManyInstructions { found: ctx.machine_code_line_number as usize, maximum: max_num_ram_cells });
+    }
+
+    //Instructions such as DATA take up multiple RAM cells.
+    let mut machine_code: Vec<String> = final_build.iter()
+        .flat_map(|s| s.lines())
+        .map(|s| s.to_string())
+        .collect();
+
+    if config.append_crc16 {
+        append_crc16(&mut machine_code, config.word_bits);
+    }
+
+    //Includes the END and the CRC-16 added after the program.
+    if machine_code.len() > max_num_ram_cells {
+        return Err(AssemblerError::TooManyInstructions { found: machine_code.len(), maximum: max_num_ram_cells });
+    }
+
+    //The smaller budget wins when both the command line and the source set one.
+    let budget =
+        match (config.max_size, ctx.budget) {
+            (Some(max_size), Some(budget)) => Some(max_size.min(budget)),
+            (max_size, budget) => max_size.or(budget),
+        };
+
+    if let Some(budget) = budget {
+        if machine_code.len() > budget {
+            return Err(AssemblerError::BudgetExceeded { used: machine_code.len(), budget });
+        }
+    }
+
+    let mut address = 0;
+    let instructions = instructions.into_iter()
+        .zip(&final_build)
+        .map(|((line, instruction), binary_string)| {
+            let size = binary_string.lines().count();
+            let assembled = AssembledInstruction { line, address, size, instruction };
+            address += size;
+            assembled
+        })
+        .collect();
+
+    let marks = ctx.marks_to_machine_code.into_iter()
+        .map(|(mark, line)| (mark, line as usize))
+        .collect();
+
+    Ok(AssembledProgram {
+        machine_code,
+        marks,
+        mark_lines: ctx.mark_lines,
+        instructions,
+        warnings,
+        budget,
+        address_bits: config.address_bits,
+        word_bits: config.word_bits,
+        source_hash: source_hash(content),
+        metadata: ctx.metadata,
+        entry_point,
+        timing_model: config.timing_model.clone(),
+        base_address: config.base_address,
+    })
+}
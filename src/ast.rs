@@ -0,0 +1,251 @@
+//The source as a list of statements, after parsing and before encoding.
+
+use std::path::PathBuf;
+
+use tracing::instrument;
+
+use crate::assembler::{parse_line, ParseContext};
+use crate::config::Config;
+use crate::editor::Span;
+use crate::error::AssemblerError;
+use crate::instructions::Instructions;
+use crate::mark_name::{resolve_in_module, split_offset, MarkName};
+
+//An instruction with the source line it came from. The instructions of a pseudo instruction or
+//macro share the line and span that used them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpannedInstruction {
+    pub instruction: Instructions,
+    pub line: usize,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpannedMark {
+    pub name: String,
+    pub line: usize,
+    pub span: Span,
+}
+
+//A line starting with a directive such as .budget, with the words after it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpannedDirective {
+    pub name: String,
+    pub arguments: Vec<String>,
+    pub line: usize,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Statement {
+    Instruction(SpannedInstruction),
+    Mark(SpannedMark),
+    Directive(SpannedDirective),
+}
+
+impl Statement {
+    pub fn line(&self) -> usize {
+        match self {
+            Statement::Instruction(SpannedInstruction { line, .. })
+            | Statement::Mark(SpannedMark { line, .. })
+            | Statement::Directive(SpannedDirective { line, .. }) => *line,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Program {
+    pub statements: Vec<Statement>,
+    pub source_file: Option<PathBuf>,
+}
+
+impl Program {
+    //Every instruction with its source line, the form the checks in analysis take.
+    pub fn instructions(&self) -> Vec<(usize, Instructions)> {
+        self.statements.iter()
+            .filter_map(|statement| match statement {
+                Statement::Instruction(spanned) => Some((spanned.line, spanned.instruction.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn marks(&self) -> impl Iterator<Item = &SpannedMark> {
+        self.statements.iter().filter_map(|statement| match statement {
+            Statement::Mark(mark) => Some(mark),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VisitResult {
+    Continue,
+    //Ends the walk, the statements after this one are not visited.
+    Stop,
+}
+
+//A pass over the statements of a program in order, such as one of the checks in analysis.
+pub trait AstVisitor {
+    fn visit_instruction(&mut self, instr: &SpannedInstruction) -> VisitResult;
+
+    fn visit_mark(&mut self, _mark: &SpannedMark) -> VisitResult {
+        VisitResult::Continue
+    }
+
+    fn visit_directive(&mut self, _directive: &SpannedDirective) -> VisitResult {
+        VisitResult::Continue
+    }
+
+    fn walk_program(&mut self, program: &Program) {
+        for statement in &program.statements {
+            let result =
+                match statement {
+                    Statement::Instruction(instruction) => self.visit_instruction(instruction),
+                    Statement::Mark(mark) => self.visit_mark(mark),
+                    Statement::Directive(directive) => self.visit_directive(directive),
+                };
+
+            if let VisitResult::Stop = result {
+                break;
+            }
+        }
+    }
+}
+
+//The text of the line without the whitespace around it.
+fn statement_span(content: &str, line: &str) -> Span {
+    let text = line.trim();
+    let start = text.as_ptr() as usize - content.as_ptr() as usize;
+    Span { start, end: start + text.len() }
+}
+
+//Parses every line of the source, carrying on after a line that fails so all of its errors are
+//found at once. Jumps to marks that are never defined are errors as well.
+pub fn parse_program(content: &str, config: &Config) -> Result<Program, Vec<AssemblerError>> {
+    parse_statements(content, &mut ParseContext::new(config))
+}
+
+//Same as parse_program, leaving the marks and everything else the directives set in ctx.
+#[instrument(skip_all)]
+pub fn parse_statements(content: &str, ctx: &mut ParseContext) -> Result<Program, Vec<AssemblerError>> {
+    let mut statements = Vec::new();
+    let mut errors = Vec::new();
+    //Statements written inside a .module, with the module.
+    let mut module_statements = Vec::new();
+
+    for line in content.lines() {
+        let in_macro = ctx.macro_definition.is_some();
+        let skipped = !ctx.features.is_active();
+        let defined_marks = ctx.mark_lines.len();
+        let instructions =
+            match parse_line(line, ctx) {
+                Ok(instructions) => instructions,
+                //Every line after it would be past the end of RAM too.
+                Err(error @ AssemblerError::AddressSpaceExhausted { .. }) => {
+                    errors.push(error);
+                    break;
+                }
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+
+        //The lines of a .feature block that is left out are not part of the program.
+        if skipped && !in_macro {
+            continue;
+        }
+
+        let line_number = ctx.real_line_number;
+        let span = statement_span(content, line);
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            //The lines of a macro become statements where the macro is used.
+            [first, ..] if in_macro && *first != ".endm" => {}
+            ["MARK", name] => {
+                //parse_line has already checked the name.
+                let name = MarkName::parse(name).map_or(name.to_string(), |mark| mark.in_module(ctx.module.as_deref()).to_string());
+                statements.push(Statement::Mark(SpannedMark { name, line: line_number, span }));
+                continue;
+            }
+            [first, arguments @ ..] if first.starts_with('.') => {
+                if let Some(module) = &ctx.module {
+                    module_statements.push((statements.len(), module.clone()));
+                }
+                statements.push(Statement::Directive(SpannedDirective {
+                    name: first.to_string(),
+                    arguments: arguments.iter().map(|argument| argument.to_string()).collect(),
+                    line: line_number,
+                    span,
+                }));
+            }
+            _ => {}
+        }
+
+        if let Some(module) = &ctx.module {
+            module_statements.extend((statements.len()..statements.len() + instructions.len()).map(|index| (index, module.clone())));
+        }
+        statements.extend(instructions.into_iter().map(|instruction| {
+            Statement::Instruction(SpannedInstruction { instruction, line: line_number, span })
+        }));
+
+        //Marks defined by pseudo instructions and macros, a jump can land anywhere in the line.
+        for (name, _) in ctx.mark_lines.iter().skip(defined_marks) {
+            statements.push(Statement::Mark(SpannedMark { name: name.clone(), line: line_number, span }));
+        }
+    }
+
+    if let Some((_, definition)) = &ctx.macro_definition {
+        errors.push(AssemblerError::UnterminatedMacro { line: definition.line });
+    }
+
+    for block in ctx.features.open_blocks() {
+        errors.push(AssemblerError::UnterminatedBlock { line: block.line, directive: ".endfeature" });
+    }
+
+    for block in &ctx.control_blocks {
+        errors.push(AssemblerError::UnterminatedBlock { line: block.line, directive: block.kind.end_directive() });
+    }
+
+    for (index, module) in module_statements {
+        match &mut statements[index] {
+            Statement::Instruction(spanned) => {
+                if let Some(mark) = spanned.instruction.referenced_mark_mut() {
+                    resolve_in_module(mark, &module, &ctx.marks_to_machine_code);
+                }
+            }
+            //The mark of an .entry is looked up in its module the same way.
+            Statement::Directive(directive) if directive.name == ".entry" => {
+                if let (Some(mark), Some((entry, _))) = (directive.arguments.first_mut(), &mut ctx.entry) {
+                    resolve_in_module(mark, &module, &ctx.marks_to_machine_code);
+                    *entry = mark.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    //Each missing mark is only reported once.
+    let mut missing_marks: Vec<&str> = Vec::new();
+    for statement in &statements {
+        if let Statement::Instruction(spanned) = statement {
+            match spanned.instruction.referenced_mark().map(|mark| split_offset(mark).0) {
+                Some(mark) if !ctx.marks_to_machine_code.contains_key(mark) && !missing_marks.contains(&mark) => missing_marks.push(mark),
+                _ => {}
+            }
+        }
+    }
+    if let Some((mark, _)) = &ctx.entry {
+        if !ctx.marks_to_machine_code.contains_key(mark) && !missing_marks.contains(&mark.as_str()) {
+            missing_marks.push(mark);
+        }
+    }
+    errors.extend(missing_marks.into_iter().map(|mark| AssemblerError::MarkNotFound { mark: mark.to_string() }));
+
+    match errors.is_empty() {
+        true => Ok(Program { statements, source_file: None }),
+        false => Err(errors),
+    }
+}
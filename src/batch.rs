@@ -0,0 +1,115 @@
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use log::info;
+use rayon::prelude::*;
+
+use crate::assemble_str;
+use crate::config::Config;
+use crate::error::AssemblerError;
+use crate::output::line_ending::{LineEnding, LineEndingWriter};
+use crate::output::{atomic_write, OutputTarget};
+use crate::warning::Warning;
+
+//One line of a batch manifest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchEntry {
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+//Reads a manifest with one "INPUT OUTPUT" pair per line. Empty lines and lines starting with #
+//are skipped.
+pub fn parse_manifest(content: &str) -> Result<Vec<BatchEntry>, AssemblerError> {
+    let mut entries = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => {}
+            [first, ..] if first.starts_with('#') => {}
+            [input, output] => entries.push(BatchEntry { input: PathBuf::from(input), output: PathBuf::from(output) }),
+            _ => return Err(AssemblerError::InvalidManifestLine { line: i + 1, text: line.trim().to_string() }),
+        }
+    }
+
+    Ok(entries)
+}
+
+//Assembles one entry of the manifest in the format set by the config, returning its warnings.
+pub fn assemble_entry(entry: &BatchEntry, config: &Config) -> Result<Vec<Warning>, AssemblerError> {
+    let content = fs::read_to_string(&entry.input)
+        .map_err(|error| AssemblerError::Io { path: entry.input.clone(), error })?;
+
+    let program = assemble_str(&content, config)?;
+
+    let target = OutputTarget { format: config.format, path: entry.output.clone(), coe_radix: None };
+    let line_ending = if config.format.is_text() { config.line_ending } else { LineEnding::Lf };
+    atomic_write(&entry.output, |file| target.writer().write(&program, &mut LineEndingWriter::new(file, line_ending)))
+        .map_err(|error| AssemblerError::Io { path: entry.output.clone(), error })?;
+    info!("Wrote {}", entry.output.display());
+
+    Ok(program.warnings)
+}
+
+#[derive(Debug)]
+pub struct BatchResult {
+    pub result: Result<Vec<Warning>, AssemblerError>,
+    //Time taken to read, assemble and write the entry.
+    pub elapsed: Duration,
+}
+
+fn timed_entry(entry: &BatchEntry, config: &Config) -> BatchResult {
+    let start = Instant::now();
+    let result = assemble_entry(entry, config);
+    BatchResult { result, elapsed: start.elapsed() }
+}
+
+//Assembles every entry with the same config, in the order of the manifest. One failing entry
+//does not stop the others. Entries are independent so with parallel set they are assembled at
+//the same time, the results are still in the order of the manifest.
+pub fn run_batch(entries: &[BatchEntry], config: &Config, parallel: bool) -> Vec<BatchResult> {
+    if parallel {
+        entries.par_iter()
+            .map(|entry| timed_entry(entry, config))
+            .collect()
+    } else {
+        entries.iter()
+            .map(|entry| timed_entry(entry, config))
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct BatchSummary {
+    pub assembled: usize,
+    pub failed: usize,
+    pub warnings: usize,
+}
+
+impl BatchSummary {
+    pub fn new(results: &[BatchResult]) -> Self {
+        let mut summary = BatchSummary::default();
+
+        for result in results {
+            match &result.result {
+                Ok(warnings) => {
+                    summary.assembled += 1;
+                    summary.warnings += warnings.len();
+                }
+                Err(_) => summary.failed += 1,
+            }
+        }
+
+        summary
+    }
+}
+
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} assembled, {} failed, {} warnings", self.assembled, self.failed, self.warnings)
+    }
+}
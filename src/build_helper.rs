@@ -0,0 +1,56 @@
+//Helpers for assembling programs from a build.rs script.
+//
+//    fn main() {
+//        logical_cpu_assembler::build_helper::assemble_file("programs/multiplication").unwrap();
+//    }
+//
+//The crate can then embed the program with include_ms!("multiplication"). Add the assembler to
+//[build-dependencies] with default-features = false, which leaves out the command line tool.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::assembler::assemble_str;
+use crate::config::Config;
+use crate::error::AssemblerError;
+use crate::output::bin::{BinWriter, BinaryFormat};
+use crate::output::{atomic_write, OutputWriter};
+
+/// Assembles `asm_path` into `$OUT_DIR/<file stem>.bin` and tells cargo to rerun the
+/// build script when the source changes. Returns the path of the written binary.
+pub fn assemble_file(asm_path: impl AsRef<Path>) -> Result<PathBuf, AssemblerError> {
+    let asm_path = asm_path.as_ref();
+
+    println!("cargo:rerun-if-changed={}", asm_path.display());
+
+    let out_dir = env::var_os("OUT_DIR").map(PathBuf::from).ok_or_else(||
+        AssemblerError::Io {
+            path: asm_path.to_path_buf(),
+            error: io::Error::new(io::ErrorKind::NotFound, "OUT_DIR is not set, assemble_file must be called from build.rs"),
+        }
+    )?;
+
+    let content = fs::read_to_string(asm_path).map_err(|error|
+        AssemblerError::Io { path: asm_path.to_path_buf(), error }
+    )?;
+
+    let program = assemble_str(&content, &Config::default())?;
+
+    let file_name = asm_path.file_stem().unwrap_or(asm_path.as_os_str());
+    let output_path = out_dir.join(file_name).with_extension("bin");
+
+    atomic_write(&output_path, |file| BinWriter { format: BinaryFormat::V1 }.write(&program, file))
+        .map_err(|error| AssemblerError::Io { path: output_path.clone(), error })?;
+
+    Ok(output_path)
+}
+
+/// Includes a program assembled by [`assemble_file`] as a `&'static [u8]`.
+#[macro_export]
+macro_rules! include_ms {
+    ($name:literal) => {
+        include_bytes!(concat!(env!("OUT_DIR"), "/", $name, ".bin")) as &'static [u8]
+    };
+}
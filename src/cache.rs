@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+
+pub const CACHE_FILE_NAME: &str = ".assembler.cache";
+
+//What a previous run read and wrote, assembling again is skipped when nothing changed.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub input_hashes: BTreeMap<PathBuf, [u8; 32]>,
+    //Options change the output as much as the inputs do.
+    pub config_hash: [u8; 32],
+    //Hash of every output file, in the order they are written.
+    pub output_hash: [u8; 32],
+}
+
+pub fn hash_file(path: &Path) -> io::Result<[u8; 32]> {
+    Ok(Sha256::digest(fs::read(path)?).into())
+}
+
+pub fn hash_inputs(inputs: &[&Path]) -> io::Result<BTreeMap<PathBuf, [u8; 32]>> {
+    inputs.iter()
+        .map(|path| Ok((path.to_path_buf(), hash_file(path)?)))
+        .collect()
+}
+
+pub fn hash_config(config: &Config) -> [u8; 32] {
+    Sha256::digest(format!("{:?}", config)).into()
+}
+
+//Fails when any of the outputs can not be read, such as before the first run.
+pub fn hash_outputs(outputs: &[&Path]) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    for path in outputs {
+        hasher.update(fs::read(path)?);
+    }
+
+    Ok(hasher.finalize().into())
+}
+
+//The entries of every input assembled with the cache file in the same directory, keyed by the
+//input path as it was given. Empty when there is no cache yet or it can not be read.
+fn load_all(cache_dir: &Path) -> BTreeMap<PathBuf, CacheEntry> {
+    fs::read_to_string(cache_dir.join(CACHE_FILE_NAME)).ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+//Returns None when the input has not been assembled with the cache yet.
+pub fn load(cache_dir: &Path, input: &Path) -> Option<CacheEntry> {
+    load_all(cache_dir).remove(input)
+}
+
+//Replaces the entry of the input, keeping the entries of the other inputs.
+pub fn save(cache_dir: &Path, input: &Path, entry: CacheEntry) -> io::Result<()> {
+    let mut entries = load_all(cache_dir);
+    entries.insert(input.to_path_buf(), entry);
+
+    fs::create_dir_all(cache_dir)?;
+    fs::write(cache_dir.join(CACHE_FILE_NAME), serde_json::to_string_pretty(&entries)?)
+}
@@ -0,0 +1,49 @@
+//Checksums added to the machine code so a programmed ROM can be checked.
+
+use crate::output::to_bytes;
+
+const CRC16_POLYNOMIAL: u16 = 0x1021;
+
+//CRC-16/CCITT of the bytes, starting from 0xFFFF without reflecting the input or output.
+pub fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc =
+                match crc & 0x8000 {
+                    0 => crc << 1,
+                    _ => (crc << 1) ^ CRC16_POLYNOMIAL,
+                };
+        }
+    }
+
+    crc
+}
+
+//Adds the CRC-16 of the machine code as two more RAM cells, one byte each with the high byte
+//first.
+pub fn append_crc16(machine_code: &mut Vec<String>, word_bits: usize) {
+    let crc = crc16(&to_bytes(machine_code));
+
+    for byte in crc.to_be_bytes() {
+        machine_code.push(format!("{:0width$b}", byte, width = word_bits));
+    }
+}
+
+//Whether the last two RAM cells hold the CRC-16 of the cells before them, as added by
+//append_crc16.
+pub fn verify_crc16(machine_code: &[String]) -> bool {
+    let (program, checksum) =
+        match machine_code.len().checked_sub(2) {
+            Some(split) => machine_code.split_at(split),
+            None => return false,
+        };
+
+    let checksum: Vec<u8> = checksum.iter()
+        .filter_map(|cell| u8::from_str_radix(cell, 2).ok())
+        .collect();
+
+    checksum == crc16(&to_bytes(program)).to_be_bytes()
+}
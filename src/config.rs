@@ -0,0 +1,409 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+use serde::Deserialize;
+
+use crate::output::line_ending::LineEnding;
+use crate::output::{OutputFormat, OutputTarget};
+use crate::instructions::{Instructions, Register, INSTRUCTION_SET};
+use crate::isa::Isa;
+use crate::timing::TimingModel;
+use crate::warning::WarnKind;
+
+pub const CONFIG_FILE_NAME: &str = ".assembler.toml";
+
+pub const ENV_PREFIX: &str = "LCPU_";
+pub const ENV_FORMAT: &str = "LCPU_FORMAT";
+pub const ENV_ADDRESS_BITS: &str = "LCPU_ADDRESS_BITS";
+pub const ENV_WORD_BITS: &str = "LCPU_WORD_BITS";
+pub const ENV_STRICT: &str = "LCPU_STRICT";
+pub const ENV_SCRATCH_REG: &str = "LCPU_SCRATCH_REG";
+
+pub const DEFAULT_ADDRESS_BITS: usize = 8;
+pub const DEFAULT_WORD_BITS: usize = 8;
+pub const ADDRESS_BITS_RANGE: RangeInclusive<usize> = 4..=16;
+pub const WORD_BITS_RANGE: RangeInclusive<usize> = 4..=32;
+//Width of a register operand in an instruction.
+pub const REGISTER_BITS: usize = 2;
+pub const DEFAULT_COMMENT_CHARS: &[&str] = &["#"];
+//CLF, which is harmless anywhere a NOP is needed.
+pub const DEFAULT_NOP_OPCODE: u8 = 0b01100000;
+pub const DEFAULT_BREAKPOINT_OPCODE: u8 = 0xFF;
+//The low REGISTER_BITS bits hold the register of the DBG.
+pub const DEFAULT_DEBUG_OPCODE: u8 = 0b11110000;
+//Most breakpoints a debugger is expected to handle.
+pub const DEFAULT_MAX_BREAKPOINTS: usize = 8;
+
+//A single configuration layer, any field left as None falls through to the next layer.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PartialConfig {
+    pub address_bits: Option<usize>,
+    pub word_bits: Option<usize>,
+    pub scratch_reg: Option<Register>,
+    pub format: Option<OutputFormat>,
+    pub strict: Option<bool>,
+    pub output: Option<Vec<OutputTarget>>,
+    pub comment_chars: Option<Vec<String>>,
+}
+
+impl PartialConfig {
+    //Values already set in self take priority over the values in lower.
+    pub fn or(self, lower: PartialConfig) -> PartialConfig {
+        PartialConfig {
+            address_bits: self.address_bits.or(lower.address_bits),
+            word_bits: self.word_bits.or(lower.word_bits),
+            scratch_reg: self.scratch_reg.or(lower.scratch_reg),
+            format: self.format.or(lower.format),
+            strict: self.strict.or(lower.strict),
+            output: self.output.or(lower.output),
+            comment_chars: self.comment_chars.or(lower.comment_chars),
+        }
+    }
+}
+
+//How much the optimizer may change the program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OptLevel {
+    #[default]
+    None,
+    Basic,
+    Full,
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub address_bits: usize,
+    pub word_bits: usize,
+    //Temporary register available to instructions that expand into several others.
+    pub scratch_reg: Register,
+    pub format: OutputFormat,
+    pub strict: bool,
+    pub optimization_level: OptLevel,
+    //Opcode written for NOP, which the CPU does not have.
+    pub nop_opcode: u8,
+    //Files written in addition to the one given on the command line.
+    pub outputs: Vec<OutputTarget>,
+    //Replaces the built in instructions when set, only set from the command line.
+    pub isa: Option<Isa>,
+    //Placed after branches that are not followed by a non branching instruction, only set from
+    //the command line.
+    pub delay_slot_fill: Option<Instructions>,
+    //Most RAM cells the program may use, only set from the command line.
+    pub max_size: Option<usize>,
+    pub disabled_warnings: Vec<WarnKind>,
+    //A line starting with any of these is a comment, such as # or ;.
+    pub comment_chars: Vec<String>,
+    //Written by .breakpoint, only set from the command line.
+    pub breakpoint_opcode: u8,
+    //More .breakpoint directives than this give a warning, only set from the command line.
+    pub max_breakpoints: usize,
+    //Written by DBG, only set from the command line.
+    pub debug_opcode: u8,
+    //Writes .breakpoint as a NOP and leaves out DBG, only set from the command line.
+    pub release: bool,
+    //Add the CRC-16 of the program as its last two RAM cells, only set from the command line.
+    pub append_crc16: bool,
+    //Names of the .feature blocks to assemble, only set from the command line.
+    pub features: Vec<String>,
+    //Ends the lines of text outputs, only set from the command line.
+    pub line_ending: LineEnding,
+    //Address the program is loaded at, added to the address after every jump. Only set from the
+    //command line.
+    pub base_address: usize,
+    //Cycles taken by each instruction, only set from the command line.
+    pub timing_model: TimingModel,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::from(PartialConfig::default())
+    }
+}
+
+impl From<PartialConfig> for Config {
+    fn from(partial: PartialConfig) -> Self {
+        Config {
+            address_bits: partial.address_bits.unwrap_or(DEFAULT_ADDRESS_BITS),
+            word_bits: partial.word_bits.unwrap_or(DEFAULT_WORD_BITS),
+            scratch_reg: partial.scratch_reg.unwrap_or(Register::R3),
+            format: partial.format.unwrap_or(OutputFormat::Ms),
+            strict: partial.strict.unwrap_or(false),
+            optimization_level: OptLevel::default(),
+            nop_opcode: DEFAULT_NOP_OPCODE,
+            outputs: partial.output.unwrap_or_default(),
+            isa: None,
+            delay_slot_fill: None,
+            max_size: None,
+            disabled_warnings: Vec::new(),
+            comment_chars: partial.comment_chars.unwrap_or_else(|| DEFAULT_COMMENT_CHARS.iter().map(|chars| chars.to_string()).collect()),
+            breakpoint_opcode: DEFAULT_BREAKPOINT_OPCODE,
+            max_breakpoints: DEFAULT_MAX_BREAKPOINTS,
+            debug_opcode: DEFAULT_DEBUG_OPCODE,
+            release: false,
+            append_crc16: false,
+            features: Vec::new(),
+            line_ending: LineEnding::Lf,
+            base_address: 0,
+            timing_model: TimingModel::default(),
+        }
+    }
+}
+
+//Name used by library users that build the config in code.
+pub type AssemblerConfig = Config;
+
+impl Config {
+    pub fn builder() -> AssemblerConfigBuilder {
+        AssemblerConfigBuilder { config: Config::default() }
+    }
+
+    //The text after the comment characters when the line is a comment.
+    pub fn comment<'a>(&self, line: &'a str) -> Option<&'a str> {
+        let line = line.trim_start();
+        self.comment_chars.iter().find_map(|chars| line.strip_prefix(chars.as_str()))
+    }
+
+    //Every option that does not fit with the others, not only the first. A jump address has to
+    //fit in a single RAM cell.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        let mut invalid = |reason: String| errors.push(ConfigError::Invalid { reason });
+
+        if !ADDRESS_BITS_RANGE.contains(&self.address_bits) {
+            invalid(format!(
+                "address bits must be from {} to {}, found {}",
+                ADDRESS_BITS_RANGE.start(), ADDRESS_BITS_RANGE.end(), self.address_bits,
+            ));
+        }
+
+        if !WORD_BITS_RANGE.contains(&self.word_bits) {
+            invalid(format!(
+                "word bits must be from {} to {}, found {}",
+                WORD_BITS_RANGE.start(), WORD_BITS_RANGE.end(), self.word_bits,
+            ));
+        }
+
+        if self.address_bits > self.word_bits {
+            invalid(format!("address bits of {} do not fit in the word bits of {}", self.address_bits, self.word_bits));
+        }
+
+        if Register::index(self.scratch_reg.clone()) >= 1 << REGISTER_BITS {
+            invalid(format!("scratch register {} does not fit in {} bits", self.scratch_reg, REGISTER_BITS));
+        }
+
+        if let Some(max_size) = self.max_size {
+            if self.address_bits < usize::BITS as usize && max_size > 1 << self.address_bits {
+                invalid(format!("max size of {} is more than the {} RAM cells {} address bits reach", max_size, 1usize << self.address_bits, self.address_bits));
+            }
+        }
+
+        //Lines start with an instruction, directive, macro or MARK, none of which may be read as
+        //a comment.
+        for chars in &self.comment_chars {
+            if chars.is_empty() || chars.contains(char::is_whitespace) || chars.starts_with(|c: char| c == '.' || c == '_' || c.is_alphanumeric()) {
+                invalid(format!("comment characters {:?} could be the start of an instruction or directive", chars));
+            }
+        }
+
+        if self.word_bits < 8 && self.nop_opcode as usize >= 1 << self.word_bits {
+            invalid(format!("NOP opcode {:#x} does not fit in the word bits of {}", self.nop_opcode, self.word_bits));
+        }
+
+        if self.debug_opcode.trailing_zeros() < REGISTER_BITS as u32 {
+            invalid(format!("DBG opcode {:#010b} does not leave the low {} bits for the register", self.debug_opcode, REGISTER_BITS));
+        }
+
+        for mnemonic in self.timing_model.cycles.keys() {
+            let custom = self.isa.as_ref().is_some_and(|isa| isa.find(mnemonic).is_some());
+            if !custom && !INSTRUCTION_SET.iter().any(|info| info.mnemonic == mnemonic) {
+                invalid(format!("the timing model gives the cycles of {}, which is not an instruction", mnemonic));
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+//Builds a Config starting from the defaults.
+pub struct AssemblerConfigBuilder {
+    config: Config,
+}
+
+impl AssemblerConfigBuilder {
+    pub fn address_bits(mut self, address_bits: u8) -> Self {
+        self.config.address_bits = address_bits as usize;
+        self
+    }
+
+    pub fn word_bits(mut self, word_bits: u8) -> Self {
+        self.config.word_bits = word_bits as usize;
+        self
+    }
+
+    pub fn scratch_reg(mut self, scratch_reg: Register) -> Self {
+        self.config.scratch_reg = scratch_reg;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.config.strict = strict;
+        self
+    }
+
+    pub fn optimization_level(mut self, optimization_level: OptLevel) -> Self {
+        self.config.optimization_level = optimization_level;
+        self
+    }
+
+    pub fn output_format(mut self, format: OutputFormat) -> Self {
+        self.config.format = format;
+        self
+    }
+
+    pub fn nop_opcode(mut self, nop_opcode: u8) -> Self {
+        self.config.nop_opcode = nop_opcode;
+        self
+    }
+
+    pub fn comment_chars(mut self, comment_chars: Vec<String>) -> Self {
+        self.config.comment_chars = comment_chars;
+        self
+    }
+
+    pub fn max_size(mut self, max_size: Option<usize>) -> Self {
+        self.config.max_size = max_size;
+        self
+    }
+
+    //Fails with the first problem found, Config::validate gives every one of them.
+    pub fn build(self) -> Result<AssemblerConfig, ConfigError> {
+        self.config.validate().map_err(|mut errors| errors.remove(0))?;
+        Ok(self.config)
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io { path: PathBuf, error: io::Error },
+    Parse { path: PathBuf, error: toml::de::Error },
+    InvalidEnv { name: String, value: String },
+    InvalidIsa { path: PathBuf, reason: String },
+    Invalid { reason: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, error } => {
+                write!(f, "Unable to read config file {}: {}", path.display(), error)
+            }
+            ConfigError::Parse { path, error } => {
+                write!(f, "Invalid config file {}: {}", path.display(), error)
+            }
+            ConfigError::InvalidEnv { name, value } => {
+                write!(f, "Invalid value {} for environment variable {}.", value, name)
+            }
+            ConfigError::InvalidIsa { path, reason } => {
+                write!(f, "Invalid instruction set {}: {}.", path.display(), reason)
+            }
+            ConfigError::Invalid { reason } => {
+                write!(f, "Invalid options, {}.", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+//Searches start and each of its parents for the config file.
+pub fn find_config_file(start: &Path) -> Option<PathBuf> {
+    start.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+pub fn from_file(path: &Path) -> Result<PartialConfig, ConfigError> {
+    let content = fs::read_to_string(path).map_err(|error|
+        ConfigError::Io { path: path.to_path_buf(), error }
+    )?;
+
+    toml::from_str(&content).map_err(|error|
+        ConfigError::Parse { path: path.to_path_buf(), error }
+    )
+}
+
+//Loads the config file closest to the current directory, if there is one.
+pub fn from_current_dir() -> Result<PartialConfig, ConfigError> {
+    let current_dir = std::env::current_dir().map_err(|error|
+        ConfigError::Io { path: PathBuf::from("."), error }
+    )?;
+
+    match find_config_file(&current_dir) {
+        Some(path) => {
+            debug!("Using config file {}.", path.display());
+            from_file(&path)
+        }
+        None => {
+            debug!("No {} found, using defaults.", CONFIG_FILE_NAME);
+            Ok(PartialConfig::default())
+        }
+    }
+}
+
+fn parse_env<T>(name: &str, value: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Option<T>, ConfigError> {
+    match parse(value) {
+        Some(v) => Ok(Some(v)),
+        None => Err(ConfigError::InvalidEnv { name: name.to_string(), value: value.to_string() }),
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" => Some(true),
+        "0" | "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn from_vars(vars: impl Iterator<Item=(String, String)>) -> Result<PartialConfig, ConfigError> {
+    let mut partial = PartialConfig::default();
+
+    for (name, value) in vars {
+        if !name.starts_with(ENV_PREFIX) {
+            continue;
+        }
+
+        match name.as_str() {
+            ENV_FORMAT => {
+                partial.format = parse_env(&name, &value, |v| v.parse().ok())?;
+            }
+            ENV_ADDRESS_BITS => {
+                partial.address_bits = parse_env(&name, &value, |v| v.parse().ok())?;
+            }
+            ENV_WORD_BITS => {
+                partial.word_bits = parse_env(&name, &value, |v| v.parse().ok())?;
+            }
+            ENV_STRICT => {
+                partial.strict = parse_env(&name, &value, parse_bool)?;
+            }
+            ENV_SCRATCH_REG => {
+                partial.scratch_reg = parse_env(&name, &value, |v| v.parse().ok())?;
+            }
+            _ => warn!("unrecognized environment variable {} is ignored.", name),
+        }
+    }
+
+    Ok(partial)
+}
+
+pub fn from_env() -> Result<PartialConfig, ConfigError> {
+    from_vars(std::env::vars())
+}
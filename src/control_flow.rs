@@ -0,0 +1,122 @@
+//Structured control flow, written as directives that expand into jumps to marks made with
+//fresh_mark. N is different for every mark in the program. FLAGS are letters
+//from CAEZ or a name defined with .flagset, and the NAME of a block is optional. When given, the
+//directive that closes the block must name it too, which catches blocks closed in the wrong
+//order.
+//
+//The body of an .if is skipped when any of the flags are set.
+//
+//  .if FLAGS NAME    JIF FLAGS __internal_if_skip_N
+//  ...               ...
+//  .endif NAME       MARK __internal_if_skip_N
+//
+//The body of a .while runs until any of the flags are set when the loop starts over.
+//
+//  .while FLAGS NAME MARK __internal_while_N
+//                    JIF FLAGS __internal_while_done_M
+//  ...               ...
+//  .endwhile NAME    JMP __internal_while_N
+//                    MARK __internal_while_done_M
+
+use crate::assembler::{fresh_mark, resolve_flags, ParseContext};
+use crate::error::AssemblerError;
+use crate::instructions::Instructions;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockKind {
+    If,
+    While,
+}
+
+impl BlockKind {
+    pub fn end_directive(&self) -> &'static str {
+        match self {
+            BlockKind::If => ".endif",
+            BlockKind::While => ".endwhile",
+        }
+    }
+}
+
+//An .if or .while that has not been closed yet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControlBlock {
+    pub kind: BlockKind,
+    //Start of a .while, jumped to by its .endwhile.
+    pub loop_mark: Option<String>,
+    //Defined by the directive that closes the block.
+    pub end_mark: String,
+    pub name: Option<String>,
+    pub line: usize,
+}
+
+fn open(kind: BlockKind, words: &[&str], ctx: &mut ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    if words.len() != 2 && words.len() != 3 {
+        return Err(AssemblerError::InvalidFormatting { line: ctx.real_line_number });
+    }
+
+    let (carry, a_larger, equal, zero) = resolve_flags(words[1], ctx)?;
+
+    let (loop_mark, mark) =
+        match kind {
+            BlockKind::If => (None, fresh_mark("if_skip", ctx)),
+            BlockKind::While => {
+                let start = fresh_mark("while", ctx);
+                ctx.define_mark(&start, ctx.machine_code_line_number + 1);
+                (Some(start), fresh_mark("while_done", ctx))
+            }
+        };
+
+    ctx.control_blocks.push(ControlBlock {
+        kind,
+        loop_mark,
+        end_mark: mark.clone(),
+        name: words.get(2).map(|name| name.to_string()),
+        line: ctx.real_line_number,
+    });
+
+    let jump = Instructions::JumpIf { carry, a_larger, equal, zero, mark };
+    ctx.machine_code_line_number += jump.size(ctx.config.word_bits) as i32;
+
+    Ok(vec![jump])
+}
+
+fn close(kind: BlockKind, words: &[&str], ctx: &mut ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    if words.len() > 2 {
+        return Err(AssemblerError::InvalidFormatting { line: ctx.real_line_number });
+    }
+
+    let unmatched = AssemblerError::UnmatchedBlockEnd { line: ctx.real_line_number, directive: words[0].to_string() };
+    let block =
+        match ctx.control_blocks.last() {
+            Some(block) if block.kind == kind && block.name.as_deref() == words.get(1).copied() => ctx.control_blocks.pop().unwrap(),
+            _ => return Err(unmatched),
+        };
+
+    let instructions =
+        match block.loop_mark {
+            Some(mark) => {
+                let jump = Instructions::JumpAddress { mark };
+                ctx.machine_code_line_number += jump.size(ctx.config.word_bits) as i32;
+                vec![jump]
+            }
+            None => Vec::new(),
+        };
+
+    ctx.define_mark(&block.end_mark, ctx.machine_code_line_number + 1);
+
+    Ok(instructions)
+}
+
+//Returns None when the words are not a control flow directive.
+pub fn expand(words: &[&str], ctx: &mut ParseContext) -> Result<Option<Vec<Instructions>>, AssemblerError> {
+    let instructions =
+        match words[0] {
+            ".if" => open(BlockKind::If, words, ctx)?,
+            ".while" => open(BlockKind::While, words, ctx)?,
+            ".endif" => close(BlockKind::If, words, ctx)?,
+            ".endwhile" => close(BlockKind::While, words, ctx)?,
+            _ => return Ok(None),
+        };
+
+    Ok(Some(instructions))
+}
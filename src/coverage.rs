@@ -0,0 +1,138 @@
+//Coverage of a program from an execution trace, a file with the address of every executed
+//instruction on its own line, in decimal or in hex starting with 0x.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use indexmap::IndexMap;
+
+use crate::assembler::AssembledProgram;
+use crate::config::Config;
+use crate::error::AssemblerError;
+use crate::stats::percentage;
+
+pub fn parse_trace(content: &str, config: &Config) -> Result<Vec<usize>, AssemblerError> {
+    let maximum = usize::pow(2, config.address_bits as u32);
+    let mut addresses = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let address =
+            match text.strip_prefix("0x") {
+                Some(hex) => usize::from_str_radix(hex, 16),
+                None => text.parse(),
+            };
+
+        match address {
+            Ok(address) if address < maximum => addresses.push(address),
+            _ => return Err(AssemblerError::InvalidAddress { line: i + 1, address: text.to_string(), maximum }),
+        }
+    }
+
+    Ok(addresses)
+}
+
+#[derive(Debug)]
+pub struct Coverage {
+    //Times each instruction of the program was executed, in the same order.
+    pub instruction_hits: Vec<usize>,
+    //Times execution reached the address of each mark.
+    pub mark_hits: IndexMap<String, usize>,
+    //Source lines of each run of instructions that never executed, first and last.
+    pub unreached: Vec<(usize, usize)>,
+}
+
+impl Coverage {
+    pub fn new(program: &AssembledProgram, trace: &[usize]) -> Self {
+        let mut hits_by_address = vec![0; program.machine_code.len()];
+        for address in trace {
+            if let Some(hits) = hits_by_address.get_mut(*address) {
+                *hits += 1;
+            }
+        }
+
+        let instruction_hits: Vec<usize> = program.instructions.iter()
+            .map(|assembled| hits_by_address[assembled.address])
+            .collect();
+
+        let mark_hits = program.marks.iter()
+            .map(|(mark, address)| (mark.clone(), hits_by_address.get(*address).copied().unwrap_or(0)))
+            .collect();
+
+        let mut unreached: Vec<(usize, usize)> = Vec::new();
+        let mut previous_unreached = false;
+        for (assembled, hits) in program.instructions.iter().zip(&instruction_hits) {
+            if *hits == 0 {
+                match unreached.last_mut() {
+                    Some((_, last)) if previous_unreached => *last = assembled.line,
+                    _ => unreached.push((assembled.line, assembled.line)),
+                }
+            }
+            previous_unreached = *hits == 0;
+        }
+
+        Coverage { instruction_hits, mark_hits, unreached }
+    }
+
+    pub fn marks_covered(&self) -> usize {
+        self.mark_hits.values().filter(|hits| **hits > 0).count()
+    }
+
+    pub fn instructions_executed(&self) -> usize {
+        self.instruction_hits.iter().filter(|hits| **hits > 0).count()
+    }
+
+    //Every instruction with the times it executed, ##### for instructions that never did.
+    pub fn write_listing(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        for (assembled, hits) in program.instructions.iter().zip(&self.instruction_hits) {
+            let count =
+                match hits {
+                    0 => "#####".to_string(),
+                    hits => hits.to_string(),
+                };
+
+            for (mark, _) in program.marks.iter().filter(|(_, address)| **address == assembled.address) {
+                writeln!(out, "{:>8}  {:>4}  MARK {}", self.mark_hits[mark], "", mark)?;
+            }
+
+            writeln!(out, "{:>8}  {:>4}  {}", count, assembled.address, assembled.instruction)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marks = self.mark_hits.len();
+        let instructions = self.instruction_hits.len();
+
+        writeln!(f, "Marks: {} of {} covered ({:.1}%)", self.marks_covered(), marks, percentage(self.marks_covered(), marks))?;
+        for (mark, hits) in &self.mark_hits {
+            writeln!(f, "  {:<16} {}", mark, hits)?;
+        }
+
+        writeln!(
+            f,
+            "Instructions: {} of {} executed ({:.1}%)",
+            self.instructions_executed(), instructions, percentage(self.instructions_executed(), instructions),
+        )?;
+
+        if self.unreached.is_empty() {
+            write!(f, "Every instruction was reached.")
+        } else {
+            let regions: Vec<String> = self.unreached.iter()
+                .map(|(first, last)| match first == last {
+                    true => format!("line {}", first),
+                    false => format!("lines {}-{}", first, last),
+                })
+                .collect();
+
+            write!(f, "Never reached: {}", regions.join(", "))
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//Directives checked once every mark has an address.
+//
+//  .assert EXPR "message"
+//
+//fails with the message when EXPR is 0. EXPR is made of numbers, written in decimal or with 0x or
+//0b, and mark names, which are their addresses, joined by the operators below from the lowest
+//precedence to the highest. Comparisons and ! give 1 for true and 0 for false.
+//
+//  ||
+//  &&
+//  == !=
+//  < <= > >=
+//  + -
+//  * / %
+//  ! - (unary)
+
+use indexmap::IndexMap;
+
+use crate::error::AssemblerError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assertion {
+    pub expression: String,
+    pub message: String,
+    pub line: usize,
+}
+
+//Reads the words after .assert, the message in double quotes is left out when it is not given.
+pub fn parse_assert(arguments: &str, line: usize) -> Result<Assertion, AssemblerError> {
+    let arguments = arguments.trim();
+
+    let (expression, message) =
+        match arguments.find('"') {
+            Some(quote) => {
+                let message = arguments[quote + 1..].strip_suffix('"')
+                    .filter(|message| !message.contains('"'))
+                    .ok_or(AssemblerError::InvalidFormatting { line })?;
+                (arguments[..quote].trim(), message.to_string())
+            }
+            None => (arguments, arguments.to_string()),
+        };
+
+    if expression.is_empty() {
+        return Err(AssemblerError::InvalidFormatting { line });
+    }
+
+    Ok(Assertion { expression: expression.to_string(), message, line })
+}
+
+pub fn assert(assertion: &Assertion, marks: &IndexMap<String, i32>) -> Result<(), AssemblerError> {
+    match evaluate(&assertion.expression, marks, assertion.line)? {
+        0 => Err(AssemblerError::AssertionFailed { message: assertion.message.clone(), line: assertion.line }),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(i64),
+    Name(String),
+    Operator(&'static str),
+    Open,
+    Close,
+}
+
+//Longest first so <= is not read as < followed by =.
+const OPERATORS: &[&str] = &["||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "!"];
+
+fn tokenize(expression: &str, line: usize) -> Result<Vec<Token>, AssemblerError> {
+    let invalid = || AssemblerError::InvalidExpression { line, expression: expression.to_string() };
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let length =
+            if c == '(' || c == ')' {
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+                1
+            } else if let Some(operator) = OPERATORS.iter().find(|operator| rest.starts_with(**operator)) {
+                tokens.push(Token::Operator(operator));
+                operator.len()
+            } else if c.is_ascii_alphanumeric() || c == '_' {
+                //Mark names may have a module.
+                let word = &rest[..rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '.').unwrap_or(rest.len())];
+                let number =
+                    if let Some(hex) = word.strip_prefix("0x") {
+                        Some(i64::from_str_radix(hex, 16).map_err(|_| invalid())?)
+                    } else if let Some(binary) = word.strip_prefix("0b") {
+                        Some(i64::from_str_radix(binary, 2).map_err(|_| invalid())?)
+                    } else if c.is_ascii_digit() {
+                        Some(word.parse().map_err(|_| invalid())?)
+                    } else {
+                        None
+                    };
+
+                tokens.push(number.map_or_else(|| Token::Name(word.to_string()), Token::Number));
+                word.len()
+            } else {
+                return Err(invalid());
+            };
+
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+//Operators of each precedence level, from the lowest.
+const BINARY_LEVELS: &[&[&str]] = &[&["||"], &["&&"], &["==", "!="], &["<", "<=", ">", ">="], &["+", "-"], &["*", "/", "%"]];
+
+struct Evaluator<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    marks: &'a IndexMap<String, i32>,
+    expression: &'a str,
+    line: usize,
+}
+
+impl Evaluator<'_> {
+    fn invalid(&self) -> AssemblerError {
+        AssemblerError::InvalidExpression { line: self.line, expression: self.expression.to_string() }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn binary(&mut self, level: usize) -> Result<i64, AssemblerError> {
+        if level == BINARY_LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut value = self.binary(level + 1)?;
+        while let Some(Token::Operator(operator)) = self.tokens.get(self.position).cloned() {
+            if !BINARY_LEVELS[level].contains(&operator) {
+                break;
+            }
+            self.position += 1;
+
+            let right = self.binary(level + 1)?;
+            value =
+                match operator {
+                    "||" => (value != 0 || right != 0) as i64,
+                    "&&" => (value != 0 && right != 0) as i64,
+                    "==" => (value == right) as i64,
+                    "!=" => (value != right) as i64,
+                    "<" => (value < right) as i64,
+                    "<=" => (value <= right) as i64,
+                    ">" => (value > right) as i64,
+                    ">=" => (value >= right) as i64,
+                    "+" => value.checked_add(right).ok_or_else(|| self.invalid())?,
+                    "-" => value.checked_sub(right).ok_or_else(|| self.invalid())?,
+                    "*" => value.checked_mul(right).ok_or_else(|| self.invalid())?,
+                    "/" => value.checked_div(right).ok_or_else(|| self.invalid())?,
+                    _ => value.checked_rem(right).ok_or_else(|| self.invalid())?,
+                };
+        }
+
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<i64, AssemblerError> {
+        match self.next() {
+            Some(Token::Operator("-")) => self.unary()?.checked_neg().ok_or_else(|| self.invalid()),
+            Some(Token::Operator("!")) => Ok((self.unary()? == 0) as i64),
+            Some(Token::Number(number)) => Ok(number),
+            Some(Token::Name(name)) => {
+                self.marks.get(&name).map(|address| *address as i64).ok_or(AssemblerError::MarkNotFound { mark: name })
+            }
+            Some(Token::Open) => {
+                let value = self.binary(0)?;
+                match self.next() {
+                    Some(Token::Close) => Ok(value),
+                    _ => Err(self.invalid()),
+                }
+            }
+            _ => Err(self.invalid()),
+        }
+    }
+}
+
+pub fn evaluate(expression: &str, marks: &IndexMap<String, i32>, line: usize) -> Result<i64, AssemblerError> {
+    let mut evaluator = Evaluator { tokens: tokenize(expression, line)?, position: 0, marks, expression, line };
+
+    let value = evaluator.binary(0)?;
+    match evaluator.position == evaluator.tokens.len() {
+        true => Ok(value),
+        false => Err(evaluator.invalid()),
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::error::AssemblerError;
+use crate::instructions::{data_truncated, Instructions, Register};
+
+fn invalid(address: usize, cell: &str) -> AssemblerError {
+    AssemblerError::InvalidMachineCode { address, cell: cell.to_string() }
+}
+
+//Reads the cell following an instruction, such as the value of DATA or the address of JMP.
+fn operand(machine_code: &[String], address: usize) -> Result<usize, AssemblerError> {
+    let cell = machine_code.get(address).ok_or_else(|| invalid(address, ""))?;
+
+    usize::from_str_radix(cell, 2).map_err(|_| invalid(address, cell))
+}
+
+//Reads machine code written as hex, such as 8000 0110 2200 42 from a memory dump, into one RAM
+//cell for each pair of hex digits. The groups between separators may hold any number of pairs, an
+//empty separator reads the whole text as one group.
+pub fn parse_hex(hex: &str, separator: &str) -> Result<Vec<String>, AssemblerError> {
+    let groups: Vec<&str> =
+        match separator {
+            "" => vec![hex],
+            separator => hex.split(separator).collect(),
+        };
+
+    let mut machine_code = Vec::new();
+    for group in groups.iter().map(|group| group.trim()).filter(|group| !group.is_empty()) {
+        if group.len() % 2 != 0 || !group.is_ascii() {
+            return Err(invalid(machine_code.len(), group));
+        }
+
+        for pair in group.as_bytes().chunks(2) {
+            let pair = std::str::from_utf8(pair).expect("The group is ASCII.");
+            let byte = u8::from_str_radix(pair, 16).map_err(|_| invalid(machine_code.len(), pair))?;
+            machine_code.push(format!("{:08b}", byte));
+        }
+    }
+
+    Ok(machine_code)
+}
+
+//Maps each address to a single mark name, the first name alphabetically wins when several marks
+//share an address.
+fn names_by_address(symbols: &HashMap<String, usize>) -> HashMap<usize, &str> {
+    let mut names: HashMap<usize, &str> = HashMap::new();
+    for (mark, address) in symbols {
+        let name = names.entry(*address).or_insert(mark);
+        if mark.as_str() < *name {
+            *name = mark;
+        }
+    }
+
+    names
+}
+
+//Decodes machine code back into instructions along with the address of each one. Jump targets
+//are numeric addresses unless symbols gives a mark name for them.
+pub fn disassemble(machine_code: &[String], symbols: Option<&HashMap<String, usize>>) -> Result<Vec<(usize, Instructions)>, AssemblerError> {
+    let names = symbols.map(names_by_address).unwrap_or_default();
+    let target = |address: usize| match names.get(&address) {
+        Some(name) => name.to_string(),
+        None => address.to_string(),
+    };
+
+    let mut instructions = Vec::new();
+    let mut address = 0;
+    while address < machine_code.len() {
+        let cell = machine_code[address].as_str();
+        if cell.len() != 8 || !cell.chars().all(|c| c == '0' || c == '1') {
+            return Err(invalid(address, cell));
+        }
+
+        let reg_a = Register::from_binary(&cell[4..6]);
+        let reg_b = Register::from_binary(&cell[6..8]);
+
+        let (instruction, num_cells) =
+            match &cell[0..4] {
+                //END shares its encoding with AND R3 R3, the assembler only emits it for END.
+                _ if cell == "11001111" => (Instructions::End, 1),
+                "1000" => (Instructions::Add { reg_a, reg_b }, 1),
+                "1001" => (Instructions::Shr { reg_a, reg_b }, 1),
+                "1010" => (Instructions::Shl { reg_a, reg_b }, 1),
+                "1011" => (Instructions::Not { reg_a, reg_b }, 1),
+                "1100" => (Instructions::And { reg_a, reg_b }, 1),
+                "1101" => (Instructions::Or { reg_a, reg_b }, 1),
+                "1110" => (Instructions::XOr { reg_a, reg_b }, 1),
+                "0001" => (Instructions::Store { reg_a, reg_b }, 1),
+                "0000" => (Instructions::Load { reg_a, reg_b }, 1),
+                "0010" if &cell[4..6] == "00" => {
+                    let data = operand(machine_code, address + 1)?;
+                    (Instructions::Data { reg: reg_b, data }, 2)
+                }
+                "0011" if &cell[4..6] == "00" => (Instructions::JumpRegister { reg: reg_b }, 1),
+                "0100" if &cell[4..8] == "0000" => {
+                    let mark = target(operand(machine_code, address + 1)?);
+                    (Instructions::JumpAddress { mark }, 2)
+                }
+                "0101" if &cell[4..8] != "0000" => {
+                    let mark = target(operand(machine_code, address + 1)?);
+                    let flag = |i: usize| &cell[i..i + 1] == "1";
+                    (Instructions::JumpIf { carry: flag(4), a_larger: flag(5), equal: flag(6), zero: flag(7), mark }, 2)
+                }
+                "0110" if &cell[4..8] == "0000" => (Instructions::ClearFlags, 1),
+                _ => return Err(invalid(address, cell)),
+            };
+
+        instructions.push((address, instruction));
+        address += num_cells;
+    }
+
+    Ok(instructions)
+}
+
+//Writes disassembled instructions as assembly source, with a MARK before each address named in
+//symbols.
+pub fn write_source(instructions: &[(usize, Instructions)], symbols: Option<&HashMap<String, usize>>, out: &mut dyn Write) -> io::Result<()> {
+    let mut marks: Vec<(&String, &usize)> = symbols.map(|symbols| symbols.iter().collect()).unwrap_or_default();
+    marks.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+
+    let mut marks = marks.into_iter().peekable();
+    for (address, instruction) in instructions {
+        while let Some((mark, _)) = marks.next_if(|(_, mark_address)| *mark_address <= address) {
+            writeln!(out, "MARK {}", mark)?;
+        }
+
+        writeln!(out, "{}", instruction)?;
+    }
+
+    for (mark, _) in marks {
+        writeln!(out, "MARK {}", mark)?;
+    }
+
+    Ok(())
+}
+
+//An instruction whose machine code does not decode back into it, a bug in Instructions::binary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncodingMismatch {
+    //Position of the instruction in the list given to validate_encoding.
+    pub index: usize,
+    pub instruction: Instructions,
+    pub binary: String,
+    //None when the machine code could not be decoded at all.
+    pub decoded: Option<Instructions>,
+}
+
+impl fmt::Display for EncodingMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.decoded {
+            Some(decoded) => write!(f, "{} was encoded as {:?}, which decodes to {}.", self.instruction, self.binary, decoded),
+            None => write!(f, "{} was encoded as {:?}, which does not decode.", self.instruction, self.binary),
+        }
+    }
+}
+
+//The instruction as the disassembler reads it back. Jump targets are left out as the disassembler
+//only knows their address. None for instructions it can not tell apart from others.
+fn comparable(instruction: &Instructions) -> Option<Instructions> {
+    let comparable =
+        match instruction.clone() {
+            //Any opcode can be used for NOP.
+            Instructions::Nop { .. }
+            | Instructions::Breakpoint { .. }
+            | Instructions::Debug { .. }
+            | Instructions::PcRelative { .. }
+            | Instructions::Custom { .. } => return None,
+            Instructions::And { reg_a: Register::R3, reg_b: Register::R3 } => Instructions::End,
+            Instructions::Data { reg, data } => Instructions::Data { reg, data: data_truncated(data, 8) },
+            Instructions::JumpAddress { .. } => Instructions::JumpAddress { mark: String::new() },
+            Instructions::JumpIf { carry, a_larger, equal, zero, .. } => {
+                Instructions::JumpIf { carry, a_larger, equal, zero, mark: String::new() }
+            }
+            //Encoded as a JIF testing the other flags.
+            Instructions::JumpIfNot { carry, a_larger, equal, zero, .. } => {
+                Instructions::JumpIf { carry: !carry, a_larger: !a_larger, equal: !equal, zero: !zero, mark: String::new() }
+            }
+            instruction => instruction,
+        };
+
+    Some(comparable)
+}
+
+//Decodes the machine code of each instruction, one string per instruction with its RAM cells on
+//separate lines as made by the second pass, and returns every one that does not decode back into
+//the same instruction. Only 8 bit RAM cells can be decoded.
+pub fn validate_encoding(instrs: &[Instructions], binary: &[String]) -> Vec<EncodingMismatch> {
+    instrs.iter()
+        .zip(binary)
+        .enumerate()
+        .filter_map(|(index, (instruction, binary))| {
+            let expected = comparable(instruction)?;
+            let cells: Vec<String> = binary.lines().map(|cell| cell.to_string()).collect();
+
+            let decoded =
+                match disassemble(&cells, None).as_deref() {
+                    Ok([(_, decoded)]) => Some(decoded.clone()),
+                    _ => None,
+                };
+
+            match decoded.as_ref().and_then(comparable) {
+                Some(comparable) if comparable == expected => None,
+                _ => Some(EncodingMismatch { index, instruction: instruction.clone(), binary: binary.clone(), decoded }),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,356 @@
+//Questions editors ask about the source, such as what is under the cursor. Positions are byte
+//offsets into the source.
+
+use std::collections::HashMap;
+
+use strum::IntoEnumIterator;
+
+use crate::assemble_str;
+use crate::config::Config;
+use crate::error::AssemblerError;
+use crate::info::DIRECTIVES;
+use crate::instructions::{InstructionInfo, Operand, Register, INSTRUCTION_SET};
+use crate::mark_name::{split_offset, MarkName};
+use crate::pseudo::PSEUDO_INSTRUCTIONS;
+
+//Byte range of a token in the source, end is exclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset <= self.end
+    }
+}
+
+//A mark name written in the source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarkToken<'a> {
+    pub name: &'a str,
+    pub span: Span,
+    //A MARK line, otherwise the mark is used by a jump or .entry.
+    pub definition: bool,
+}
+
+//Every word of the source with its span, line by line.
+pub fn line_words(source: &str) -> Vec<Vec<(&str, Span)>> {
+    source.lines()
+        .map(|line| {
+            line.split_whitespace()
+                .map(|word| {
+                    let start = word.as_ptr() as usize - source.as_ptr() as usize;
+                    (word, Span { start, end: start + word.len() })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+//The word the offset is in or directly after.
+pub fn word_at(source: &str, offset: usize) -> Option<(&str, Span)> {
+    line_words(source).into_iter()
+        .flatten()
+        .find(|(_, span)| span.contains(offset))
+}
+
+//Every MARK definition, jump to a mark and .entry, in the order they appear. The offset of a jump
+//such as JMP LOOP+2 is not part of the token. Marks used in .assert expressions, macro arguments
+//and instructions from --isa are not found.
+pub fn mark_tokens(source: &str) -> Vec<MarkToken<'_>> {
+    let mut tokens = Vec::new();
+
+    for words in line_words(source) {
+        let mark =
+            match words.as_slice() {
+                [("MARK", _), (name, span)] => Some((*name, *span, true)),
+                [("JMP" | ".entry", _), (name, span)] => Some((split_offset(name).0, *span, false)),
+                [("JIF" | "JIFN" | "PCREL", _), _, (name, span)] => Some((split_offset(name).0, *span, false)),
+                _ => None,
+            };
+
+        if let Some((name, span, definition)) = mark {
+            tokens.push(MarkToken { name, span: Span { start: span.start, end: span.start + name.len() }, definition });
+        }
+    }
+
+    tokens
+}
+
+//The MARK definition of a mark and every jump to it, covering only the name.
+pub fn find_references(source: &str, mark_name: &str) -> Vec<Span> {
+    mark_tokens(source).into_iter()
+        .filter(|token| token.name == mark_name)
+        .map(|token| token.span)
+        .collect()
+}
+
+//Where the mark under the offset is defined.
+pub fn mark_definition(source: &str, offset: usize) -> Option<Span> {
+    let (name, _) = word_at(source, offset)?;
+
+    mark_tokens(source).into_iter()
+        .find(|token| token.definition && token.name == name)
+        .map(|token| token.span)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompletionKind {
+    Instruction,
+    Register,
+    Mark,
+    Directive,
+    //A name defined with .flagset.
+    Constant,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    pub text: String,
+    pub kind: CompletionKind,
+    pub detail: String,
+}
+
+impl Completion {
+    fn new(text: &str, kind: CompletionKind, detail: impl Into<String>) -> Self {
+        Completion { text: text.to_string(), kind, detail: detail.into() }
+    }
+}
+
+//Line of the offset, counting from 1.
+pub fn line_number(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count() + 1
+}
+
+pub(crate) fn operands(mnemonic: &str) -> &'static [Operand] {
+    if let Some(info) = INSTRUCTION_SET.iter().find(|info| info.mnemonic == mnemonic) {
+        return info.operands;
+    }
+
+    match mnemonic {
+        "BT" | "BSET" | "BCLR" => &[Operand::Reg, Operand::Imm],
+        "PCREL" => &[Operand::Reg, Operand::Mark],
+        _ if PSEUDO_INSTRUCTIONS.iter().any(|(pseudo, _)| *pseudo == mnemonic) => &[Operand::Reg, Operand::Reg],
+        _ => &[],
+    }
+}
+
+fn operand_completions(source: &str, operand: Operand) -> Vec<Completion> {
+    match operand {
+        Operand::Reg => {
+            Register::iter()
+                .map(|reg| Completion::new(&reg.to_string(), CompletionKind::Register, format!("Encoded as {}.", Register::binary(reg))))
+                .collect()
+        }
+        Operand::Mark => {
+            mark_tokens(source).into_iter()
+                .filter(|token| token.definition)
+                .map(|token| Completion::new(token.name, CompletionKind::Mark, format!("Defined on line {}.", line_number(source, token.span.start))))
+                .collect()
+        }
+        Operand::Flags => {
+            line_words(source).into_iter()
+                .filter_map(|words| match words.as_slice() {
+                    [(".flagset", _), (name, _), (flags, _)] => Some(Completion::new(name, CompletionKind::Constant, format!("Flags {}.", flags))),
+                    _ => None,
+                })
+                .collect()
+        }
+        Operand::Imm => Vec::new(),
+    }
+}
+
+//What can be written at the offset. The first word of a line is an instruction or directive,
+//the words after it are completed by the operands the instruction takes.
+pub fn completions(source: &str, byte_offset: usize) -> Vec<Completion> {
+    let byte_offset = byte_offset.min(source.len());
+    let line_start = source[..byte_offset].rfind('\n').map_or(0, |newline| newline + 1);
+    let before = &source[line_start..byte_offset];
+
+    let mut words: Vec<&str> = before.split_whitespace().collect();
+    //The word the cursor is in, empty when it is after a space.
+    let prefix =
+        match before.ends_with(char::is_whitespace) {
+            true => "",
+            false => words.pop().unwrap_or_default(),
+        };
+
+    let candidates =
+        match words.as_slice() {
+            [] => {
+                let instructions = INSTRUCTION_SET.iter()
+                    .map(|info| Completion::new(info.mnemonic, CompletionKind::Instruction, info.description));
+                let pseudo_instructions = PSEUDO_INSTRUCTIONS.iter()
+                    .map(|(mnemonic, description)| Completion::new(mnemonic, CompletionKind::Instruction, *description));
+                let directives = DIRECTIVES.iter()
+                    .filter(|directive| !directive.syntax.starts_with('#'))
+                    .map(|directive| {
+                        let name = directive.syntax.split_whitespace().next().unwrap_or_default();
+                        Completion::new(name, CompletionKind::Directive, directive.description)
+                    });
+
+                instructions.chain(pseudo_instructions).chain(directives).collect()
+            }
+            [mnemonic, written @ ..] => {
+                match operands(mnemonic).get(written.len()) {
+                    Some(operand) => operand_completions(source, *operand),
+                    None => Vec::new(),
+                }
+            }
+        };
+
+    candidates.into_iter()
+        .filter(|completion| completion.text.starts_with(prefix))
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HoverInfo {
+    pub title: String,
+    pub description: String,
+    //How the word is written in machine code.
+    pub encoding: Option<String>,
+}
+
+//The bits of an instruction, with letters for the bits filled in by its operands. An immediate
+//or mark takes up the next RAM cell.
+fn encoding_format(info: &InstructionInfo) -> String {
+    let mut encoding = info.opcode.to_string();
+    let registers = info.operands.iter().filter(|operand| **operand == Operand::Reg).count();
+
+    for operand in info.operands {
+        match operand {
+            Operand::Reg if registers == 2 && encoding.len() == info.opcode.len() => encoding.push_str("AA"),
+            Operand::Reg if registers == 2 => encoding.push_str("BB"),
+            Operand::Reg => encoding.push_str("RR"),
+            Operand::Flags => encoding.push_str("CAEZ"),
+            Operand::Imm | Operand::Mark => {}
+        }
+    }
+
+    match info.operands.last() {
+        Some(Operand::Imm) => format!("{}, then the value in the next RAM cell", encoding),
+        Some(Operand::Mark) => format!("{}, then the address of the mark in the next RAM cell", encoding),
+        _ => encoding,
+    }
+}
+
+fn mark_hover(source: &str, name: &str) -> Option<HoverInfo> {
+    let definition = mark_tokens(source).into_iter().find(|token| token.definition && token.name == name)?;
+    let line = line_number(source, definition.span.start);
+
+    //The address is only known when the whole program assembles.
+    let description =
+        match assemble_str(source, &Config::default()).ok().and_then(|program| program.marks.get(name).copied()) {
+            Some(address) => format!("Mark defined on line {}, at address {}.", line, address),
+            None => format!("Mark defined on line {}.", line),
+        };
+
+    Some(HoverInfo { title: name.to_string(), description, encoding: None })
+}
+
+//What the word under the offset is: an instruction, pseudo instruction, directive, register or
+//mark.
+pub fn hover(source: &str, byte_offset: usize) -> Option<HoverInfo> {
+    let (word, span) = word_at(source, byte_offset)?;
+    let first_word = line_words(source).into_iter()
+        .filter_map(|words| words.first().copied())
+        .any(|(_, first)| first == span);
+
+    if first_word {
+        if let Some(info) = INSTRUCTION_SET.iter().find(|info| info.mnemonic == word) {
+            return Some(HoverInfo { title: word.to_string(), description: info.description.to_string(), encoding: Some(encoding_format(info)) });
+        }
+
+        if let Some((_, description)) = PSEUDO_INSTRUCTIONS.iter().find(|(mnemonic, _)| *mnemonic == word) {
+            return Some(HoverInfo {
+                title: word.to_string(),
+                description: format!("{} Pseudo instruction, expands into several instructions.", description),
+                encoding: None,
+            });
+        }
+
+        return DIRECTIVES.iter()
+            .find(|directive| directive.syntax.split_whitespace().next() == Some(word))
+            .map(|directive| HoverInfo { title: directive.syntax.to_string(), description: directive.description.to_string(), encoding: None });
+    }
+
+    if let Some(reg) = Register::iter().find(|reg| reg.to_string() == word) {
+        return Some(HoverInfo {
+            title: word.to_string(),
+            description: format!("Register {}.", reg),
+            encoding: Some(Register::binary(reg).to_string()),
+        });
+    }
+
+    mark_hover(source, word)
+}
+
+//Whether a word can be used as a mark. Numbers are not allowed as patches read them as
+//addresses, and registers or words starting with # or . would be read as something else.
+pub fn is_valid_mark_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains(char::is_whitespace)
+        && !name.starts_with(['#', '.'])
+        && MarkName::parse(name).is_ok_and(|mark| !mark.is_internal())
+        && !name.chars().all(|c| c.is_ascii_digit())
+        && !Register::iter().any(|reg| reg.to_string() == name)
+}
+
+//Renames the MARK definition of a mark and every use of it found by mark_tokens.
+pub fn rename_mark(source: &str, old_name: &str, new_name: &str) -> Result<String, AssemblerError> {
+    if !is_valid_mark_name(new_name) {
+        return Err(AssemblerError::InvalidMarkName { name: new_name.to_string() });
+    }
+
+    let tokens = mark_tokens(source);
+    if !tokens.iter().any(|token| token.definition && token.name == old_name) {
+        return Err(AssemblerError::MarkNotFound { mark: old_name.to_string() });
+    }
+    if tokens.iter().any(|token| token.definition && token.name == new_name) {
+        return Err(AssemblerError::MarkAlreadyDefined { mark: new_name.to_string() });
+    }
+
+    let mut renamed = source.to_string();
+    //From the end so the spans that are left still point at the right text.
+    for span in find_references(source, old_name).iter().rev() {
+        renamed.replace_range(span.start..span.end, new_name);
+    }
+
+    Ok(renamed)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InlayHintKind {
+    //The address a MARK resolves to.
+    MarkAddress,
+    //The address a jump goes to.
+    JumpTarget,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InlayHint {
+    //Offset the hint is shown at, just after the mark name.
+    pub position: usize,
+    pub label: String,
+    pub kind: InlayHintKind,
+}
+
+//The resolved address after every mark name, such as "→ 0x05". Marks missing from marks get no
+//hint.
+pub fn inlay_hints(source: &str, marks: &HashMap<String, usize>) -> Vec<InlayHint> {
+    mark_tokens(source).into_iter()
+        .filter_map(|token| {
+            let address = marks.get(token.name)?;
+            let kind =
+                match token.definition {
+                    true => InlayHintKind::MarkAddress,
+                    false => InlayHintKind::JumpTarget,
+                };
+
+            Some(InlayHint { position: token.span.end, label: format!("\u{2192} 0x{:02X}", address), kind })
+        })
+        .collect()
+}
@@ -0,0 +1,369 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::instructions::{truncation_description, Register};
+use crate::mark_name::INTERNAL_MARK_PREFIX;
+
+#[derive(Debug)]
+pub enum AssemblerError {
+    InvalidRegister { line: usize, register: String },
+    InvalidFormatting { line: usize },
+    UnknownInstruction { line: usize, instruction: String },
+    InvalidData { line: usize, data: String },
+    DataOutOfRange { line: usize, column: usize, data: usize, truncated: usize, word_bits: usize },
+    InvalidJumpFlag { line: usize, flag: char },
+    UnknownFlagset { line: usize, name: String },
+    ScratchRegisterUsed { line: usize, register: Register },
+    BitOutOfRange { line: usize, bit: String, word_bits: usize },
+    MarkNotFound { mark: String },
+    MacroAlreadyDefined { line: usize, name: String },
+    MacroArguments { line: usize, name: String, expected: usize, found: usize },
+    UnterminatedMacro { line: usize },
+    UnknownInclude { line: usize, name: String },
+    InvalidMnemonicAlias { line: usize, alias: String, instruction: String },
+    InvalidMarkName { name: String },
+    MarkAlreadyDefined { mark: String },
+    TooManyInstructions { found: usize, maximum: usize },
+    BudgetExceeded { used: usize, budget: usize },
+    InvalidMachineCode { address: usize, cell: String },
+    InvalidSymbol { line: usize, text: String },
+    InvalidAddress { line: usize, address: String, maximum: usize },
+    InvalidManifestLine { line: usize, text: String },
+    Io { path: PathBuf, error: io::Error },
+    PcRelOutOfRange { line: usize, mark: String, offset: i64 },
+    EntryAlreadyDefined { line: usize },
+    AssertionFailed { message: String, line: usize },
+    InvalidExpression { line: usize, expression: String },
+    UnterminatedBlock { line: usize, directive: &'static str },
+    UnmatchedBlockEnd { line: usize, directive: String },
+    //Line whose instructions go past the last RAM cell.
+    AddressSpaceExhausted { at_line: usize },
+    MarkOffsetOutOfRange { mark: String, address: i64, maximum: usize },
+    ReservedMarkName { line: usize, name: String },
+    //A JIFN of all four flags, which would never jump.
+    JifnNeverJumps { line: usize },
+    //The reasons from Config::validate, one for each problem found.
+    InvalidConfig { reasons: Vec<String> },
+}
+
+impl AssemblerError {
+    //Source line the error is about, counting from 1, when it is about a single line.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            AssemblerError::InvalidRegister { line, .. }
+            | AssemblerError::InvalidFormatting { line }
+            | AssemblerError::UnknownInstruction { line, .. }
+            | AssemblerError::InvalidData { line, .. }
+            | AssemblerError::DataOutOfRange { line, .. }
+            | AssemblerError::InvalidJumpFlag { line, .. }
+            | AssemblerError::UnknownFlagset { line, .. }
+            | AssemblerError::ScratchRegisterUsed { line, .. }
+            | AssemblerError::BitOutOfRange { line, .. }
+            | AssemblerError::InvalidSymbol { line, .. }
+            | AssemblerError::InvalidAddress { line, .. }
+            | AssemblerError::InvalidManifestLine { line, .. }
+            | AssemblerError::MacroAlreadyDefined { line, .. }
+            | AssemblerError::MacroArguments { line, .. }
+            | AssemblerError::UnterminatedMacro { line }
+            | AssemblerError::UnknownInclude { line, .. }
+            | AssemblerError::InvalidMnemonicAlias { line, .. }
+            | AssemblerError::PcRelOutOfRange { line, .. }
+            | AssemblerError::EntryAlreadyDefined { line }
+            | AssemblerError::AssertionFailed { line, .. }
+            | AssemblerError::InvalidExpression { line, .. }
+            | AssemblerError::UnterminatedBlock { line, .. }
+            | AssemblerError::UnmatchedBlockEnd { line, .. }
+            | AssemblerError::ReservedMarkName { line, .. }
+            | AssemblerError::JifnNeverJumps { line }
+            | AssemblerError::AddressSpaceExhausted { at_line: line } => Some(*line),
+            AssemblerError::MarkNotFound { .. }
+            | AssemblerError::MarkOffsetOutOfRange { .. }
+            | AssemblerError::InvalidMarkName { .. }
+            | AssemblerError::MarkAlreadyDefined { .. }
+            | AssemblerError::TooManyInstructions { .. }
+            | AssemblerError::BudgetExceeded { .. }
+            | AssemblerError::InvalidMachineCode { .. }
+            | AssemblerError::InvalidConfig { .. }
+            | AssemblerError::Io { .. } => None,
+        }
+    }
+}
+
+impl AssemblerError {
+    //Code of the error, explained by the explain subcommand.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AssemblerError::InvalidRegister { .. } => "E001",
+            AssemblerError::InvalidFormatting { .. } => "E002",
+            AssemblerError::UnknownInstruction { .. } => "E003",
+            AssemblerError::InvalidData { .. } => "E004",
+            AssemblerError::DataOutOfRange { .. } => "E005",
+            AssemblerError::InvalidJumpFlag { .. } => "E006",
+            AssemblerError::UnknownFlagset { .. } => "E007",
+            AssemblerError::ScratchRegisterUsed { .. } => "E008",
+            AssemblerError::BitOutOfRange { .. } => "E009",
+            AssemblerError::MarkNotFound { .. } => "E010",
+            AssemblerError::MacroAlreadyDefined { .. } => "E011",
+            AssemblerError::MacroArguments { .. } => "E012",
+            AssemblerError::UnterminatedMacro { .. } => "E013",
+            AssemblerError::UnknownInclude { .. } => "E014",
+            AssemblerError::InvalidMnemonicAlias { .. } => "E015",
+            AssemblerError::InvalidMarkName { .. } => "E016",
+            AssemblerError::MarkAlreadyDefined { .. } => "E017",
+            AssemblerError::TooManyInstructions { .. } => "E018",
+            AssemblerError::BudgetExceeded { .. } => "E019",
+            AssemblerError::InvalidMachineCode { .. } => "E020",
+            AssemblerError::InvalidSymbol { .. } => "E021",
+            AssemblerError::InvalidAddress { .. } => "E022",
+            AssemblerError::InvalidManifestLine { .. } => "E023",
+            AssemblerError::Io { .. } => "E024",
+            AssemblerError::PcRelOutOfRange { .. } => "E025",
+            AssemblerError::EntryAlreadyDefined { .. } => "E026",
+            AssemblerError::AssertionFailed { .. } => "E027",
+            AssemblerError::InvalidExpression { .. } => "E028",
+            AssemblerError::UnterminatedBlock { .. } => "E029",
+            AssemblerError::UnmatchedBlockEnd { .. } => "E030",
+            AssemblerError::AddressSpaceExhausted { .. } => "E031",
+            AssemblerError::MarkOffsetOutOfRange { .. } => "E032",
+            AssemblerError::ReservedMarkName { .. } => "E033",
+            AssemblerError::InvalidConfig { .. } => "E034",
+            AssemblerError::JifnNeverJumps { .. } => "E035",
+        }
+    }
+
+    //The error without its code.
+    pub fn message(&self) -> String {
+        self.to_string()[self.code().len() + 2..].to_string()
+    }
+}
+
+impl fmt::Display for AssemblerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.code())?;
+
+        match self {
+            AssemblerError::InvalidRegister { line, register } => {
+                write!(f, "{} Invalid register number found of {}.", line, register)
+            }
+            AssemblerError::InvalidFormatting { line } => {
+                write!(f, "{} Invalid formatting for registers.", line)
+            }
+            AssemblerError::UnknownInstruction { line, instruction } => {
+                write!(f, "{} Unknown instruction used, {}", line, instruction)
+            }
+            AssemblerError::InvalidData { line, data } => {
+                write!(f, "{} Invalid number passed as data {}.", line, data)
+            }
+            AssemblerError::DataOutOfRange { line, column, data, truncated, word_bits } => {
+                write!(f, "{}:{} {}", line, column, truncation_description(*data, *truncated, *word_bits))
+            }
+            AssemblerError::InvalidJumpFlag { line, flag } => {
+                write!(f, "{} Invalid formatting for JIF command {}.", line, flag)
+            }
+            AssemblerError::UnknownFlagset { line, name } => {
+                write!(f, "{} Unknown flags {} for JIF, expected letters from CAEZ or a name defined with .flagset.", line, name)
+            }
+            AssemblerError::ScratchRegisterUsed { line, register } => {
+                write!(f, "{} Register {} is the scratch register and is overwritten by this instruction, use --scratch-reg to pick another.", line, register)
+            }
+            AssemblerError::BitOutOfRange { line, bit, word_bits } => {
+                write!(f, "{} Invalid bit {}, expected a number from 0 to {}.", line, bit, word_bits - 1)
+            }
+            AssemblerError::MarkNotFound { mark } => {
+                write!(f, "Mark {} not found.", mark)
+            }
+            AssemblerError::MacroAlreadyDefined { line, name } => {
+                write!(f, "{} {} is already an instruction or macro.", line, name)
+            }
+            AssemblerError::MacroArguments { line, name, expected, found } => {
+                write!(f, "{} Macro {} takes {} arguments, {} given.", line, name, expected, found)
+            }
+            AssemblerError::UnterminatedMacro { line } => {
+                write!(f, "{} .macro is never closed with .endm.", line)
+            }
+            AssemblerError::UnknownInclude { line, name } => {
+                write!(f, "{} Unknown include {}, only <std> can be included.", line, name)
+            }
+            AssemblerError::InvalidMnemonicAlias { line, alias, instruction } => {
+                write!(f, "{} Invalid .mnemonic {} {}, the alias must be a new name and the instruction must exist.", line, alias, instruction)
+            }
+            AssemblerError::InvalidMarkName { name } => {
+                write!(f, "Invalid mark name {}, expected a word that is not a register or a number, with at most one dot after a module name.", name)
+            }
+            AssemblerError::MarkAlreadyDefined { mark } => {
+                write!(f, "Mark {} is already defined.", mark)
+            }
+            AssemblerError::TooManyInstructions { found, maximum } => {
+                write!(f, "File contains too many instructions. {} found, {} maximum.", found, maximum)
+            }
+            AssemblerError::BudgetExceeded { used, budget } => {
+                write!(f, "Program uses {} RAM cells, over its budget of {}.", used, budget)
+            }
+            AssemblerError::InvalidMachineCode { address, cell } => {
+                write!(f, "Invalid machine code {} at address {}.", cell, address)
+            }
+            AssemblerError::InvalidSymbol { line, text } => {
+                write!(f, "{} Invalid symbol {}, expected a mark name followed by its address.", line, text)
+            }
+            AssemblerError::InvalidAddress { line, address, maximum } => {
+                write!(f, "{} Invalid address {}, expected a number below {}.", line, address, maximum)
+            }
+            AssemblerError::InvalidManifestLine { line, text } => {
+                write!(f, "{} Invalid manifest line {}, expected an input path followed by an output path.", line, text)
+            }
+            AssemblerError::Io { path, error } => {
+                write!(f, "{}: {}", path.display(), error)
+            }
+            AssemblerError::PcRelOutOfRange { line, mark, offset } => {
+                write!(f, "{} Mark {} is {} RAM cells away, a PCREL can only reach from -128 to 127.", line, mark, offset)
+            }
+            AssemblerError::EntryAlreadyDefined { line } => {
+                write!(f, "{} The entry point is already set by an earlier .entry.", line)
+            }
+            AssemblerError::AssertionFailed { message, line } => {
+                write!(f, "{} Assertion failed, {}.", line, message)
+            }
+            AssemblerError::InvalidExpression { line, expression } => {
+                write!(f, "{} Invalid expression {}.", line, expression)
+            }
+            AssemblerError::UnterminatedBlock { line, directive } => {
+                write!(f, "{} Block is never closed with {}.", line, directive)
+            }
+            AssemblerError::UnmatchedBlockEnd { line, directive } => {
+                write!(f, "{} {} does not close the innermost open block.", line, directive)
+            }
+            AssemblerError::AddressSpaceExhausted { at_line } => {
+                write!(f, "{} Instructions go past the last RAM cell, their addresses would wrap around to 0.", at_line)
+            }
+            AssemblerError::MarkOffsetOutOfRange { mark, address, maximum } => {
+                write!(f, "Jump to {} goes to address {}, outside of the {} RAM cells.", mark, address, maximum)
+            }
+            AssemblerError::ReservedMarkName { line, name } => {
+                write!(f, "{} Mark {} starts with {}, which is kept for marks made by the assembler.", line, name, INTERNAL_MARK_PREFIX)
+            }
+            AssemblerError::JifnNeverJumps { line } => {
+                write!(f, "{} JIFN of every flag never jumps, its JIF would test no flags.", line)
+            }
+            AssemblerError::InvalidConfig { reasons } => {
+                write!(f, "{}", reasons.join(" "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for AssemblerError {}
+
+//Longer explanation of each error code, printed by the explain subcommand.
+pub const EXPLANATIONS: &[(&str, &str)] = &[
+    ("E001", "A register operand is not one of R0, R1, R2 or R3. \
+The CPU only has four registers and their names are written in upper case. \
+Check the operand for a typo such as r0 or R4."),
+    ("E002", "The line does not have the number of operands its instruction or directive takes. \
+ALU and memory instructions take two registers, DATA takes a register and a number, MARK takes a single name. \
+Check the line against assembler info and remove or add operands."),
+    ("E003", "The first word of the line is not an instruction, pseudo instruction, macro or directive. \
+Mnemonics are written in upper case and macros must be defined before they are used. \
+Check for a typo, or add the missing .macro, .mnemonic or .include <std>."),
+    ("E004", "A number could not be read. \
+Numbers are written in decimal without a sign, such as 42. \
+Check the value for letters, a minus sign or a value too large for the assembler to hold."),
+    ("E005", "A DATA value does not fit in a RAM cell and --strict is set. \
+Only the low bits that fit in a RAM cell would be kept, which is usually a mistake. \
+Use a smaller value, a wider --word-bits, or leave out --strict to only get a warning."),
+    ("E006", "A JIF or JIFN flag is not one of C, A, E or Z. \
+The flags are carry, A larger, equal and zero and are written together, such as CZ. \
+Check the flags for a typo or use a name defined with .flagset."),
+    ("E007", "The flags of a JIF are neither flag letters nor a name defined with .flagset. \
+Flag names must be defined with .flagset before the jumps that use them. \
+Check the name for a typo or move its .flagset earlier in the program."),
+    ("E008", "An instruction that expands into several instructions uses the scratch register as an operand. \
+Pseudo instructions and standard library macros overwrite the scratch register, so its value would be lost. \
+Use another register or pick a different scratch register with --scratch-reg."),
+    ("E009", "The bit number of BT, BSET or BCLR is not a bit of a RAM cell. \
+Bits are counted from 0, the lowest bit, up to one less than the number of bits in a RAM cell. \
+Check the bit number against --word-bits."),
+    ("E010", "A jump goes to a mark that is never defined. \
+Every mark used by JMP, JIF or JIFN needs a MARK line somewhere in the program, before or after the jump. \
+Check the name for a typo or add the missing MARK line."),
+    ("E011", "A macro is defined with the name of an instruction or of another macro. \
+Macro names must be new so it is clear which one a line uses. \
+Rename the macro or remove the earlier definition."),
+    ("E012", "A macro is used with a different number of arguments than it has parameters. \
+Each parameter listed after the name in .macro needs one argument. \
+Check the use of the macro against its definition."),
+    ("E013", "A .macro is never closed. \
+Every line after .macro is part of the macro until a line with .endm. \
+Add .endm after the last line of the macro body."),
+    ("E014", "An .include names a library the assembler does not have. \
+Only the standard library can be included, written as .include <std>. \
+Check the name, including the angle brackets."),
+    ("E015", "A .mnemonic alias is already taken or its instruction does not exist. \
+The alias must not be an instruction, directive, macro or earlier alias, and the instruction must be a built in or pseudo instruction. \
+Pick a new alias or correct the name of the instruction."),
+    ("E016", "A mark name can not be used. \
+Mark names must be a single word that is not a register, a number or a word starting with # or a period. \
+A name may have one dot, between the module and the mark, such as math.multiply. \
+Pick another name for the mark."),
+    ("E017", "A mark with this name already exists. \
+Each mark must have a name of its own so jumps to it are not ambiguous. \
+Pick a name no other MARK line uses."),
+    ("E018", "The program does not fit in RAM. \
+The number of RAM cells is set by --address-bits, 256 by default, and instructions that take a value or mark use two cells. \
+Make the program smaller or raise --address-bits."),
+    ("E019", "The program uses more RAM cells than its budget. \
+The budget is set with .budget or --max-size and the smaller of the two is used. \
+Make the program smaller or raise the budget."),
+    ("E020", "A RAM cell of machine code is not a valid instruction. \
+Machine code in the ms format has one binary string per line, as wide as a RAM cell. \
+Check that the file is machine code written by the assembler and not source or another format."),
+    ("E021", "A line of a symbol file is not a mark followed by its address. \
+Symbol files are written by --symbols with one name and one decimal address per line. \
+Check the file or write it again with --symbols."),
+    ("E022", "An address is not a number within RAM. \
+Addresses are written in decimal or in hex starting with 0x and must be below the number of RAM cells. \
+Check the address against --address-bits."),
+    ("E023", "A line of a batch manifest is not an input path followed by an output path. \
+Each line lists one file to assemble and where to write it, separated by whitespace. \
+Paths with spaces can not be used, and lines starting with # are skipped."),
+    ("E024", "A file could not be read or written. \
+The message after the path gives the reason the operating system reported. \
+Check that the path exists and that it can be read or written."),
+    ("E025", "A PCREL mark is too far away. \
+The distance from the PCREL to its mark is loaded with DATA as a signed byte, so it must be from -128 to 127. \
+Move the mark closer to the PCREL or load its address with a jump instead."),
+    ("E026", "A program has more than one .entry. \
+Execution can only start at one mark, set by a single .entry anywhere in the source. \
+Remove every .entry except the one naming the mark the program starts at."),
+    ("E027", "An .assert expression is 0. \
+The expression is worked out once every mark has an address, mark names in it are their addresses. \
+Change the program so the condition holds, the message after the expression says what was expected."),
+    ("E028", "An .assert expression can not be worked out. \
+Expressions are made of numbers, mark names, parentheses and the operators || && == != < <= > >= + - * / % and !. \
+Check for a typo, a missing parenthesis or a division by zero."),
+    ("E029", "An .if or .while is never closed. \
+Every .if needs an .endif and every .while needs an .endwhile after its body. \
+Add the closing directive after the last line of the block."),
+    ("E030", "An .endif or .endwhile does not match the innermost open block. \
+Blocks close in the reverse order they were opened, an .endif closes an .if and an .endwhile closes a .while. \
+When the block was given a name, the closing directive must give the same name."),
+    ("E031", "The program runs out of RAM cells at this line. \
+The instructions on it would be placed past the last RAM cell, where the addresses wrap around to 0 and overwrite the start of the program. \
+Make the program smaller or raise --address-bits."),
+    ("E032", "A jump to a mark with an offset, such as JMP LOOP+2, goes to an address outside of RAM. \
+The offset is added to the address of the mark and the result must be from 0 up to the last RAM cell. \
+Use a smaller offset or jump to another mark."),
+    ("E033", "A MARK uses a name starting with __internal_. \
+The assembler names the marks it makes for pseudo instructions such as ROL and for .if and .while blocks this way, so a mark of the program could clash with them. \
+Rename the mark."),
+    ("E034", "The options the program is assembled with can not work together or are out of range, such as more address bits than fit in a RAM cell. \
+Each problem found is listed after the code. \
+Change the options given on the command line, in the environment or in .assembler.toml."),
+    ("E035", "A JIFN is given all four flags C, A, E and Z. \
+JIFN is encoded as a JIF of the flags that are not given, which here is no flags, so the jump could never be taken and the RAM cell would not decode as a JIF. \
+Remove the JIFN or give it fewer flags."),
+];
+
+pub fn explanation(code: &str) -> Option<&'static str> {
+    EXPLANATIONS.iter().find(|(explained, _)| *explained == code).map(|(_, explanation)| *explanation)
+}
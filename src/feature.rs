@@ -0,0 +1,42 @@
+//Conditional assembly. The lines between
+//
+//  .feature NAME
+//  ...
+//  .endfeature
+//
+//are only assembled when NAME is given with --feature. A block inside another is only assembled
+//when both are. The lines of a block that is left out are not parsed, other than its .feature and
+//.endfeature lines so the blocks inside it still close in the right place.
+
+//An open .feature block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeatureBlock {
+    pub name: String,
+    pub line: usize,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeatureStack {
+    //The innermost last.
+    blocks: Vec<FeatureBlock>,
+}
+
+impl FeatureStack {
+    pub fn push(&mut self, name: &str, line: usize, enabled: bool) {
+        self.blocks.push(FeatureBlock { name: name.to_string(), line, enabled });
+    }
+
+    pub fn pop(&mut self) -> Option<FeatureBlock> {
+        self.blocks.pop()
+    }
+
+    //Whether the lines at this point are assembled.
+    pub fn is_active(&self) -> bool {
+        self.blocks.iter().all(|block| block.enabled)
+    }
+
+    pub fn open_blocks(&self) -> &[FeatureBlock] {
+        &self.blocks
+    }
+}
@@ -0,0 +1,71 @@
+//Files the assembler reads and writes, so programs can be assembled without touching the disk.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::output::atomic_write;
+
+pub trait FileSystem: Send + Sync {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    //Replaces the file when it already exists.
+    fn write_all(&self, path: &Path, data: &[u8]) -> io::Result<()>;
+    fn exists(&self, path: &Path) -> bool;
+}
+
+pub struct RealFileSystem;
+
+impl FileSystem for RealFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn write_all(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        atomic_write(path, |file| file.write_all(data))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+//Files kept in memory, paths are compared exactly as given.
+#[derive(Debug, Default)]
+pub struct MockFileSystem {
+    files: Mutex<HashMap<PathBuf, Vec<u8>>>,
+}
+
+impl MockFileSystem {
+    pub fn new() -> Self {
+        MockFileSystem::default()
+    }
+
+    pub fn with_file(self, path: impl Into<PathBuf>, data: impl Into<Vec<u8>>) -> Self {
+        self.files.lock().unwrap().insert(path.into(), data.into());
+        self
+    }
+
+    pub fn contents(&self, path: &Path) -> Option<Vec<u8>> {
+        self.files.lock().unwrap().get(path).cloned()
+    }
+}
+
+impl FileSystem for MockFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let data = self.contents(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} does not exist", path.display())))?;
+
+        String::from_utf8(data).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    fn write_all(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.files.lock().unwrap().insert(path.to_path_buf(), data.to_vec());
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+}
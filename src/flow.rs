@@ -0,0 +1,116 @@
+//Control flow of a program split into basic blocks, runs of instructions that are only entered
+//at the top and only left at the bottom.
+
+use std::collections::BTreeSet;
+
+use indexmap::IndexMap;
+
+use crate::instructions::Instructions;
+use crate::mark_name::split_offset;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BasicBlock {
+    //Index of the first and last instruction of the block in the instructions given to new.
+    pub first: usize,
+    pub last: usize,
+    //Index of each block execution can continue in.
+    pub successors: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FlowGraph {
+    pub blocks: Vec<BasicBlock>,
+}
+
+impl FlowGraph {
+    //Builds the graph from instructions and their addresses. Jumps go to the address of their
+    //mark, or to the address written as the mark as in disassembled machine code. JMPR can go
+    //anywhere so like END it has no successors, and so does a jump to an address that holds no
+    //instruction.
+    pub fn new(instructions: &[(usize, Instructions)], marks: &IndexMap<String, usize>) -> Self {
+        let index_of = |address: usize| instructions.iter().position(|(instruction_address, _)| *instruction_address == address);
+        let target = |instruction: &Instructions| {
+            let mark = instruction.jump_mark()?;
+            let (name, offset) = split_offset(mark);
+            let address = marks.get(name).map(|address| *address as i64 + offset)
+                .and_then(|address| usize::try_from(address).ok())
+                .or_else(|| mark.parse().ok())?;
+            index_of(address)
+        };
+
+        let mut leaders = BTreeSet::new();
+        for (i, (_, instruction)) in instructions.iter().enumerate() {
+            if i == 0 {
+                leaders.insert(i);
+            }
+            if let Some(target) = target(instruction) {
+                leaders.insert(target);
+            }
+            if (instruction.is_branch() || *instruction == Instructions::End) && i + 1 < instructions.len() {
+                leaders.insert(i + 1);
+            }
+        }
+
+        let leaders: Vec<usize> = leaders.into_iter().collect();
+        let block_of = |index: usize| leaders.iter().position(|leader| *leader == index);
+
+        let blocks = leaders.iter().enumerate()
+            .map(|(i, first)| {
+                let last = leaders.get(i + 1).map_or(instructions.len(), |next| *next) - 1;
+                let (_, instruction) = &instructions[last];
+
+                let falls_through = !matches!(
+                    instruction,
+                    Instructions::End | Instructions::JumpAddress { .. } | Instructions::JumpRegister { .. }
+                );
+
+                let mut successors = Vec::new();
+                if let Some(target) = target(instruction).and_then(block_of) {
+                    successors.push(target);
+                }
+                if falls_through && i + 1 < leaders.len() && !successors.contains(&(i + 1)) {
+                    successors.push(i + 1);
+                }
+
+                BasicBlock { first: *first, last, successors }
+            })
+            .collect();
+
+        FlowGraph { blocks }
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.blocks.iter().map(|block| block.successors.len()).sum()
+    }
+
+    //Groups of blocks connected by an edge in either direction.
+    pub fn connected_components(&self) -> usize {
+        let mut component: Vec<usize> = (0..self.blocks.len()).collect();
+        fn root(component: &mut [usize], block: usize) -> usize {
+            let mut block = block;
+            while component[block] != block {
+                component[block] = component[component[block]];
+                block = component[block];
+            }
+            block
+        }
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            for successor in &block.successors {
+                let (a, b) = (root(&mut component, i), root(&mut component, *successor));
+                component[a] = b;
+            }
+        }
+
+        (0..self.blocks.len()).filter(|block| root(&mut component, *block) == *block).count()
+    }
+}
+
+//E - N + 2P, the number of independent paths through the program.
+pub fn cyclomatic_complexity(flow_graph: &FlowGraph) -> u32 {
+    let edges = flow_graph.edge_count() as i64;
+    let nodes = flow_graph.blocks.len() as i64;
+    let components = flow_graph.connected_components() as i64;
+
+    (edges - nodes + 2 * components).max(0) as u32
+}
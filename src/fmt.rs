@@ -0,0 +1,67 @@
+//Formats assembly source in one consistent style, the way rustfmt does for Rust.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatConfig {
+    //Mnemonics are padded to this many characters before their operands.
+    pub mnemonic_width: usize,
+    //Column comments start at, counting from 1.
+    pub comment_column: usize,
+    //A line starting with any of these is a comment.
+    pub comment_chars: Vec<String>,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        FormatConfig { mnemonic_width: 6, comment_column: 40, comment_chars: vec!["#".to_string()] }
+    }
+}
+
+pub struct Formatter;
+
+impl Formatter {
+    //Formats every line of the source:
+    // - Words are separated by single spaces and mnemonics padded to mnemonic_width.
+    // - Comments start at comment_column.
+    // - Each MARK starts a section, which is separated from the one before it by a blank line.
+    // - Runs of blank lines become one and blank lines at the start and end are removed.
+    pub fn format(source: &str, config: &FormatConfig) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        for line in source.lines() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            match words.as_slice() {
+                [] => {
+                    if lines.last().is_some_and(|last| !last.is_empty()) {
+                        lines.push(String::new());
+                    }
+                }
+                [first, ..] if config.comment_chars.iter().any(|chars| first.starts_with(chars.as_str())) => {
+                    let comment = line.trim();
+                    lines.push(format!("{:indent$}{}", "", comment, indent = config.comment_column.saturating_sub(1)));
+                }
+                [mnemonic, operands @ ..] => {
+                    if *mnemonic == "MARK" && lines.last().is_some_and(|last| !last.is_empty()) {
+                        lines.push(String::new());
+                    }
+
+                    if operands.is_empty() {
+                        lines.push(mnemonic.to_string());
+                    } else {
+                        //A mnemonic as long as the padding still needs a space before its operands.
+                        let width = config.mnemonic_width.max(mnemonic.len() + 1);
+                        lines.push(format!("{:<width$}{}", mnemonic, operands.join(" "), width = width));
+                    }
+                }
+            }
+        }
+
+        while lines.last().is_some_and(|last| last.is_empty()) {
+            lines.pop();
+        }
+
+        let mut formatted = lines.join("\n");
+        formatted.push('\n');
+        formatted
+    }
+}
@@ -0,0 +1,232 @@
+use std::io::{self, Write};
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use crate::assembler::ParseContext;
+use crate::config::Config;
+use crate::editor::operands;
+use crate::instructions::{InstructionInfo, Instructions, Operand, Register, INSTRUCTION_SET};
+use crate::output::OutputFormat;
+use crate::pseudo::{self, PSEUDO_INSTRUCTIONS};
+
+#[derive(Debug, Serialize)]
+pub struct RegisterInfo {
+    pub name: String,
+    pub binary: &'static str,
+    //Names given to the register with .alias.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectiveInfo {
+    pub syntax: &'static str,
+    pub description: &'static str,
+}
+
+pub const DIRECTIVES: &[DirectiveInfo] = &[
+    DirectiveInfo { syntax: "MARK m", description: "Marks a jump point named m, takes up no RAM." },
+    DirectiveInfo { syntax: ".module name", description: "Puts the marks after it in module name, so MARK m defines name.m and JMP m jumps to it." },
+    DirectiveInfo { syntax: "# comment", description: "Lines starting with # are ignored." },
+    DirectiveInfo { syntax: "# assembler: allow(kind)", description: "Turns off a warning, such as unused_mark, for this line and the next." },
+    DirectiveInfo { syntax: ".alias name reg", description: "Makes name another name for a register in the operands of instructions, such as .alias counter R0." },
+    DirectiveInfo { syntax: ".flagset name flags", description: "Names a combination of JIF flags, such as .flagset OVERFLOW CA." },
+    DirectiveInfo { syntax: ".frequency hz", description: "Records the clock speed of the CPU for timing estimates." },
+    DirectiveInfo { syntax: ".checkpoint n", description: "Warns when fewer than n RAM cells are left at this point." },
+    DirectiveInfo { syntax: ".entry m", description: "Starts execution at mark m, adding a JMP to it at address 0 when it is not already there." },
+    DirectiveInfo { syntax: ".assert expr \"message\"", description: "Fails with the message when expr, worked out once every mark has an address, is 0." },
+    DirectiveInfo { syntax: ".breakpoint", description: "Writes the --breakpoint-opcode, 0xFF by default, for a debugger to halt on. A NOP with --release." },
+    DirectiveInfo { syntax: ".if flags [name]", description: "Skips the lines up to the matching .endif when any of the flags are set." },
+    DirectiveInfo { syntax: ".endif [name]", description: "Ends the body of an .if." },
+    DirectiveInfo { syntax: ".while flags [name]", description: "Repeats the lines up to the matching .endwhile until any of the flags are set when it starts over." },
+    DirectiveInfo { syntax: ".endwhile [name]", description: "Ends the body of a .while, jumping back to its start." },
+    DirectiveInfo { syntax: ".feature name", description: "Only assembles the lines up to the matching .endfeature when --feature name is given." },
+    DirectiveInfo { syntax: ".endfeature", description: "Ends the body of a .feature." },
+    DirectiveInfo { syntax: ".budget n", description: "Fails when the program uses more than n RAM cells." },
+    DirectiveInfo { syntax: ".mnemonic alias instruction", description: "Makes alias another name for an instruction, such as .mnemonic ADDU ADD." },
+    DirectiveInfo { syntax: ".macro name params", description: "Defines a macro up to the next .endm, \\param in its body is replaced by the argument." },
+    DirectiveInfo { syntax: ".endm", description: "Ends the body of a macro." },
+    DirectiveInfo { syntax: ".include <std>", description: "Defines the standard library macros MEMCOPY, MEMSET, DELAY_CYCLES, SWAP and ABS." },
+];
+
+//One row of the opcode table.
+#[derive(Debug, Serialize)]
+pub struct OpcodeInfo {
+    pub mnemonic: &'static str,
+    //Leading bits of the encoding, empty for a pseudo instruction.
+    pub opcode: &'static str,
+    pub operands: &'static [Operand],
+    pub description: &'static str,
+    //Built in instructions a pseudo instruction expands into, in order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub expands_to: Vec<&'static str>,
+}
+
+//Every built in and pseudo instruction. The opcodes are the ones Instructions::binary encodes
+//with, and the expansion of each pseudo instruction is found by expanding it.
+pub fn opcode_table() -> Vec<OpcodeInfo> {
+    let instructions = INSTRUCTION_SET.iter().map(|info| OpcodeInfo {
+        mnemonic: info.mnemonic,
+        opcode: info.opcode,
+        operands: info.operands,
+        description: info.description,
+        expands_to: Vec::new(),
+    });
+
+    let pseudo_instructions = PSEUDO_INSTRUCTIONS.iter().map(|(mnemonic, description)| OpcodeInfo {
+        mnemonic,
+        opcode: "",
+        operands: operands(mnemonic),
+        description,
+        expands_to: expansion(mnemonic, operands(mnemonic)),
+    });
+
+    instructions.chain(pseudo_instructions).collect()
+}
+
+//Mnemonics of the instructions a pseudo instruction expands into, using R0 and R1 with the
+//default config so the scratch register is not used.
+fn expansion(mnemonic: &'static str, operands: &[Operand]) -> Vec<&'static str> {
+    let mut words = vec![mnemonic];
+    words.extend(operands.iter().enumerate().map(|(i, operand)| match operand {
+        Operand::Reg if i == 0 => "R0",
+        Operand::Reg => "R1",
+        Operand::Imm => "0",
+        Operand::Mark => "MARK",
+        Operand::Flags => "Z",
+    }));
+
+    let config = Config::default();
+    let instructions = pseudo::expand(&words, &mut ParseContext::new(&config)).ok().flatten().unwrap_or_default();
+    instructions.iter()
+        .map(|instruction| match instruction {
+            //Replaced with a DATA once every address is known.
+            Instructions::PcRelative { .. } => "DATA",
+            _ => opcode_mnemonic(instruction),
+        })
+        .collect()
+}
+
+//The mnemonic of a built in instruction as it is written in INSTRUCTION_SET.
+fn opcode_mnemonic(instruction: &Instructions) -> &'static str {
+    INSTRUCTION_SET.iter()
+        .find(|info| info.mnemonic == instruction.mnemonic())
+        .map_or("?", |info| info.mnemonic)
+}
+
+pub fn write_opcode_table(table: &[OpcodeInfo], out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "{:<8} {:<10} {:<12} Description", "Mnemonic", "Opcode", "Operands")?;
+    for row in table {
+        let opcode = if row.opcode.is_empty() { "-" } else { row.opcode };
+        write!(out, "{:<8} {:<10} {:<12} {}", row.mnemonic, opcode, operand_names(row.operands), row.description)?;
+        if !row.expands_to.is_empty() {
+            write!(out, " Expands to {}.", row.expands_to.join(", "))?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+//Summary of everything the assembler supports.
+#[derive(Debug, Serialize)]
+pub struct IsaInfo {
+    pub instructions: &'static [InstructionInfo],
+    pub registers: Vec<RegisterInfo>,
+    pub directives: &'static [DirectiveInfo],
+    pub max_program_size: usize,
+    pub output_formats: Vec<String>,
+}
+
+pub fn isa_info(config: &Config) -> IsaInfo {
+    let registers = register_info(&IndexMap::new());
+
+    let output_formats = OutputFormat::iter()
+        .map(|format| <&str>::from(format).to_string())
+        .collect();
+
+    IsaInfo {
+        instructions: INSTRUCTION_SET,
+        registers,
+        directives: DIRECTIVES,
+        max_program_size: usize::pow(2, config.address_bits as u32),
+        output_formats,
+    }
+}
+
+//Every register with the aliases given to it, in the order they were defined.
+pub fn register_info(aliases: &IndexMap<String, Register>) -> Vec<RegisterInfo> {
+    Register::iter()
+        .map(|reg| RegisterInfo {
+            name: reg.to_string(),
+            binary: Register::binary(reg.clone()),
+            aliases: aliases.iter()
+                .filter(|(_, aliased)| **aliased == reg)
+                .map(|(alias, _)| alias.clone())
+                .collect(),
+        })
+        .collect()
+}
+
+//One line per register, such as R0  00  (alias: counter).
+pub fn write_registers(registers: &[RegisterInfo], out: &mut dyn Write) -> io::Result<()> {
+    for register in registers {
+        write!(out, "{:<4}{}", register.name, register.binary)?;
+        if !register.aliases.is_empty() {
+            write!(out, "  (alias: {})", register.aliases.join(", "))?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+fn operand_names(operands: &[Operand]) -> String {
+    let names: Vec<&str> = operands.iter()
+        .map(|operand| match operand {
+            Operand::Reg => "REG",
+            Operand::Imm => "IMM",
+            Operand::Mark => "MARK",
+            Operand::Flags => "FLAGS",
+        })
+        .collect();
+
+    names.join(" ")
+}
+
+pub fn write_table(info: &IsaInfo, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "Instructions")?;
+    writeln!(out, "  {:<8} {:<10} {:<12} Description", "Mnemonic", "Opcode", "Operands")?;
+    for instruction in info.instructions {
+        writeln!(
+            out,
+            "  {:<8} {:<10} {:<12} {}",
+            instruction.mnemonic,
+            instruction.opcode,
+            operand_names(instruction.operands),
+            instruction.description,
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "Registers")?;
+    for register in &info.registers {
+        writeln!(out, "  {:<8} {}", register.name, register.binary)?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "Directives")?;
+    for directive in info.directives {
+        writeln!(out, "  {:<10} {}", directive.syntax, directive.description)?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "Maximum program size: {} RAM cells", info.max_program_size)?;
+
+    writeln!(out)?;
+    writeln!(out, "Output formats: {}", info.output_formats.join(", "))?;
+
+    Ok(())
+}
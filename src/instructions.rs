@@ -0,0 +1,528 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, EnumString};
+
+use crate::config::REGISTER_BITS;
+use crate::error::AssemblerError;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, EnumIter, EnumString)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum), value(rename_all = "verbatim"))]
+#[strum(ascii_case_insensitive)]
+pub enum Register {
+    R0,
+    R1,
+    R2,
+    R3,
+}
+
+impl Register {
+    //Number the register is encoded as.
+    pub fn index(reg: Register) -> usize {
+        usize::from_str_radix(Register::binary(reg), 2).expect("Register encodings are binary.")
+    }
+
+    pub fn binary(reg: Register) -> &'static str {
+        match reg {
+            Register::R0 => "00",
+            Register::R1 => "01",
+            Register::R2 => "10",
+            Register::R3 => "11",
+        }
+    }
+
+    pub(crate) fn reg_from_instr(reg: &str, real_line_number: usize) -> Result<Register, AssemblerError> {
+        match reg {
+            "R0" => Ok(Register::R0),
+            "R1" => Ok(Register::R1),
+            "R2" => Ok(Register::R2),
+            "R3" => Ok(Register::R3),
+            _ => Err(AssemblerError::InvalidRegister { line: real_line_number, register: reg.to_string() })
+        }
+    }
+
+    pub(crate) fn from_binary(bits: &str) -> Register {
+        match bits {
+            "00" => Register::R0,
+            "01" => Register::R1,
+            "10" => Register::R2,
+            _ => Register::R3,
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name =
+            match self {
+                Register::R0 => "R0",
+                Register::R1 => "R1",
+                Register::R2 => "R2",
+                Register::R3 => "R3",
+            };
+
+        f.pad(name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Operand {
+    Reg,
+    Imm,
+    Mark,
+    Flags,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstructionInfo {
+    pub mnemonic: &'static str,
+    //Leading bits of the encoding, the operand bits follow.
+    pub opcode: &'static str,
+    pub operands: &'static [Operand],
+    pub description: &'static str,
+}
+
+pub const INSTRUCTION_SET: &[InstructionInfo] = &[
+    InstructionInfo { mnemonic: "ADD", opcode: "1000", operands: &[Operand::Reg, Operand::Reg], description: "Add RA and RB, put answer in RB." },
+    InstructionInfo { mnemonic: "SHR", opcode: "1001", operands: &[Operand::Reg, Operand::Reg], description: "Shift RA right, put answer in RB." },
+    InstructionInfo { mnemonic: "SHL", opcode: "1010", operands: &[Operand::Reg, Operand::Reg], description: "Shift RA left, put answer in RB." },
+    InstructionInfo { mnemonic: "NOT", opcode: "1011", operands: &[Operand::Reg, Operand::Reg], description: "Invert RA, put answer in RB." },
+    InstructionInfo { mnemonic: "AND", opcode: "1100", operands: &[Operand::Reg, Operand::Reg], description: "AND RA and RB, put answer in RB." },
+    InstructionInfo { mnemonic: "OR", opcode: "1101", operands: &[Operand::Reg, Operand::Reg], description: "OR RA and RB, put answer in RB." },
+    InstructionInfo { mnemonic: "XOR", opcode: "1110", operands: &[Operand::Reg, Operand::Reg], description: "XOR RA and RB, put answer in RB." },
+    InstructionInfo { mnemonic: "ST", opcode: "0001", operands: &[Operand::Reg, Operand::Reg], description: "Store contents of register RA in RAM address in RB." },
+    InstructionInfo { mnemonic: "LD", opcode: "0000", operands: &[Operand::Reg, Operand::Reg], description: "Load contents of RAM address in RA into RB." },
+    InstructionInfo { mnemonic: "DATA", opcode: "001000", operands: &[Operand::Reg, Operand::Imm], description: "Loads data x into register RB." },
+    InstructionInfo { mnemonic: "JMPR", opcode: "001100", operands: &[Operand::Reg], description: "Jumps to address inside reg." },
+    InstructionInfo { mnemonic: "JMP", opcode: "01000000", operands: &[Operand::Mark], description: "Jumps to mark point mark." },
+    InstructionInfo { mnemonic: "JIF", opcode: "0101", operands: &[Operand::Flags, Operand::Mark], description: "Jumps to mark point mark if any of the flags C, A, E or Z are set." },
+    InstructionInfo { mnemonic: "JIFN", opcode: "0101", operands: &[Operand::Flags, Operand::Mark], description: "Jumps to mark point mark if any of the flags not given are set, encoded as a JIF of those flags." },
+    InstructionInfo { mnemonic: "CLF", opcode: "01100000", operands: &[], description: "Clears the C, A, E and Z flags." },
+    InstructionInfo { mnemonic: "END", opcode: "11001111", operands: &[], description: "Ends execution of the program." },
+    InstructionInfo { mnemonic: "DBG", opcode: "111100", operands: &[Operand::Reg], description: "Not a CPU instruction, written as the opcode set with --debug-opcode for a simulator to print the register. Left out with --release." },
+    InstructionInfo { mnemonic: "NOP", opcode: "01100000", operands: &[], description: "Not a CPU instruction, written as the opcode set with --nop-opcode, CLF by default." },
+];
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Instructions {
+    Add { reg_a: Register, reg_b: Register },
+    Shr { reg_a: Register, reg_b: Register },
+    Shl { reg_a: Register, reg_b: Register },
+    Not { reg_a: Register, reg_b: Register },
+    And { reg_a: Register, reg_b: Register },
+    Or { reg_a: Register, reg_b: Register },
+    XOr { reg_a: Register, reg_b: Register },
+    Store { reg_a: Register, reg_b: Register },
+    Load { reg_a: Register, reg_b: Register },
+    Data { reg: Register, data: usize },
+    JumpRegister { reg: Register },
+    JumpAddress { mark: String },
+    JumpIf { carry: bool, a_larger: bool, equal: bool, zero: bool, mark: String },
+    //Encoded as a JIF testing every flag that is not given.
+    JumpIfNot { carry: bool, a_larger: bool, equal: bool, zero: bool, mark: String },
+    ClearFlags,
+    End,
+    //Not an instruction of the CPU, written as the opcode set in the config.
+    Nop { opcode: u8 },
+    //Written by .breakpoint as the opcode set in the config, a debugger halts on it.
+    Breakpoint { opcode: u8 },
+    //Written by DBG as the opcode set in the config with the register in its low bits, a
+    //simulator prints the register when it reaches it.
+    Debug { opcode: u8, reg: Register },
+    //Written by PCREL, replaced with a DATA of the distance to the mark once every address is
+    //known.
+    PcRelative { reg: Register, mark: String },
+    //Instruction defined by an instruction set loaded with --isa.
+    Custom { mnemonic: String, opcode: String, operands: Vec<CustomOperand> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CustomOperand {
+    Reg(Register),
+    Flags { carry: bool, a_larger: bool, equal: bool, zero: bool },
+    Imm(usize),
+    Mark(String),
+}
+
+impl fmt::Display for CustomOperand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomOperand::Reg(reg) => write!(f, "{}", reg),
+            CustomOperand::Flags { carry, a_larger, equal, zero } => {
+                write!(f, "{}", flag_string(*carry, *a_larger, *equal, *zero))
+            }
+            CustomOperand::Imm(data) => write!(f, "{}", data),
+            CustomOperand::Mark(mark) => write!(f, "{}", mark),
+        }
+    }
+}
+
+//The flags tested by JIF.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlagSet {
+    pub carry: bool,
+    pub a_larger: bool,
+    pub equal: bool,
+    pub zero: bool,
+}
+
+impl FlagSet {
+    pub const ALL: FlagSet = FlagSet { carry: true, a_larger: true, equal: true, zero: true };
+
+    pub fn is_empty(&self) -> bool {
+        !(self.carry || self.a_larger || self.equal || self.zero)
+    }
+
+    pub fn is_subset(&self, other: &FlagSet) -> bool {
+        (!self.carry || other.carry)
+            && (!self.a_larger || other.a_larger)
+            && (!self.equal || other.equal)
+            && (!self.zero || other.zero)
+    }
+}
+
+impl fmt::Display for FlagSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", flag_string(self.carry, self.a_larger, self.equal, self.zero))
+    }
+}
+
+fn bool_char(b: bool) -> char {
+    match b {
+        true => '1',
+        false => '0',
+    }
+}
+
+fn flag_string(carry: bool, a_larger: bool, equal: bool, zero: bool) -> String {
+    let mut flags = String::new();
+    for (set, c) in [(carry, 'C'), (a_larger, 'A'), (equal, 'E'), (zero, 'Z')] {
+        if set {
+            flags.push(c);
+        }
+    }
+
+    flags
+}
+
+fn opcode(mnemonic: &str) -> &'static str {
+    INSTRUCTION_SET.iter()
+        .find(|info| info.mnemonic == mnemonic)
+        .map(|info| info.opcode)
+        .unwrap_or_else(|| panic!("{} is not in INSTRUCTION_SET.", mnemonic))
+}
+
+fn data_binary(data: usize, word_bits: usize) -> String {
+    let mut binary_data = format!("{:0width$b}", data, width = word_bits);
+    while binary_data.len() > word_bits {
+        binary_data.remove(0);
+    }
+
+    binary_data
+}
+
+//The value data_binary writes, only the low word_bits bits of data.
+pub fn data_truncated(data: usize, word_bits: usize) -> usize {
+    match 1usize.checked_shl(word_bits as u32) {
+        Some(num_values) => data & (num_values - 1),
+        None => data,
+    }
+}
+
+//Describes how a DATA value was cut down to fit in a RAM cell.
+pub fn truncation_description(data: usize, truncated: usize, word_bits: usize) -> String {
+    let discarded_bits = (usize::BITS - data.leading_zeros()) as usize - word_bits;
+    let discarded = if discarded_bits == 1 { "bit was" } else { "bits were" };
+    format!(
+        "Value {} (0x{:X}) truncated to {} (0x{:X}) by keeping only the low {} bits; {} high {} discarded.",
+        data, data, truncated, truncated, word_bits, discarded_bits, discarded,
+    )
+}
+
+impl Instructions {
+    pub fn mnemonic(&self) -> &str {
+        match self {
+            Instructions::Add { .. } => "ADD",
+            Instructions::Shr { .. } => "SHR",
+            Instructions::Shl { .. } => "SHL",
+            Instructions::Not { .. } => "NOT",
+            Instructions::And { .. } => "AND",
+            Instructions::Or { .. } => "OR",
+            Instructions::XOr { .. } => "XOR",
+            Instructions::Store { .. } => "ST",
+            Instructions::Load { .. } => "LD",
+            Instructions::Data { .. } => "DATA",
+            Instructions::JumpRegister { .. } => "JMPR",
+            Instructions::JumpAddress { .. } => "JMP",
+            Instructions::JumpIf { .. } => "JIF",
+            Instructions::JumpIfNot { .. } => "JIFN",
+            Instructions::ClearFlags => "CLF",
+            Instructions::Nop { .. } => "NOP",
+            Instructions::Breakpoint { .. } => ".breakpoint",
+            Instructions::Debug { .. } => "DBG",
+            Instructions::PcRelative { .. } => "PCREL",
+            Instructions::End => "END",
+            Instructions::Custom { mnemonic, .. } => mnemonic,
+        }
+    }
+
+    pub fn is_branch(&self) -> bool {
+        matches!(
+            self,
+            Instructions::JumpRegister { .. }
+            | Instructions::JumpAddress { .. }
+            | Instructions::JumpIf { .. }
+            | Instructions::JumpIfNot { .. }
+        )
+    }
+
+    //Plain English description of what the instruction does, with the operands filled in.
+    pub fn explanation(&self) -> String {
+        let template =
+            match self {
+                Instructions::Add { .. } => "Adds register {a} to {b} and stores the result in {b}; sets the carry, a larger, equal and zero flags.",
+                Instructions::Shr { .. } => "Shifts register {a} right by one bit and stores the result in {b}; the bit shifted out goes into the carry flag and the a larger, equal and zero flags are set.",
+                Instructions::Shl { .. } => "Shifts register {a} left by one bit and stores the result in {b}; the bit shifted out goes into the carry flag and the a larger, equal and zero flags are set.",
+                Instructions::Not { .. } => "Inverts every bit of register {a} and stores the result in {b}; sets the a larger, equal and zero flags.",
+                Instructions::And { .. } => "ANDs register {a} with {b} and stores the result in {b}; sets the a larger, equal and zero flags.",
+                Instructions::Or { .. } => "ORs register {a} with {b} and stores the result in {b}; sets the a larger, equal and zero flags.",
+                Instructions::XOr { .. } => "XORs register {a} with {b} and stores the result in {b}; sets the a larger, equal and zero flags.",
+                Instructions::Store { .. } => "Stores register {a} in RAM at the address held in {b}.",
+                Instructions::Load { .. } => "Loads the RAM cell at the address held in {a} into register {b}.",
+                Instructions::Data { .. } => "Loads the value {data} into register {reg}.",
+                Instructions::JumpRegister { .. } => "Jumps to the address held in register {reg}.",
+                Instructions::JumpAddress { .. } => "Jumps to mark {mark}.",
+                Instructions::JumpIf { .. } => "Jumps to mark {mark} if any of the flags {flags} are set, otherwise continues with the next instruction.",
+                Instructions::JumpIfNot { .. } => "Jumps to mark {mark} if any of the flags other than {flags} are set, otherwise continues with the next instruction.",
+                Instructions::ClearFlags => "Clears the carry, a larger, equal and zero flags.",
+                Instructions::Nop { .. } => "Does nothing useful, it only takes up a RAM cell and a cycle.",
+                Instructions::Breakpoint { .. } => "Halts the CPU when it is run by a debugger.",
+                Instructions::Debug { .. } => "Prints register {reg} when it is run by a simulator.",
+                Instructions::PcRelative { .. } => "Loads the distance from this instruction to mark {mark} into register {reg}.",
+                Instructions::End => "Ends execution of the program.",
+                Instructions::Custom { .. } => "Runs {mnemonic} from the loaded instruction set.",
+            };
+
+        let (a, b) =
+            match self {
+                Instructions::Add { reg_a, reg_b }
+                | Instructions::Shr { reg_a, reg_b }
+                | Instructions::Shl { reg_a, reg_b }
+                | Instructions::Not { reg_a, reg_b }
+                | Instructions::And { reg_a, reg_b }
+                | Instructions::Or { reg_a, reg_b }
+                | Instructions::XOr { reg_a, reg_b }
+                | Instructions::Store { reg_a, reg_b }
+                | Instructions::Load { reg_a, reg_b } => (reg_a.to_string(), reg_b.to_string()),
+                _ => (String::new(), String::new()),
+            };
+
+        let reg =
+            match self {
+                Instructions::Data { reg, .. }
+                | Instructions::JumpRegister { reg }
+                | Instructions::Debug { reg, .. }
+                | Instructions::PcRelative { reg, .. } => reg.to_string(),
+                _ => String::new(),
+            };
+
+        let (data, flags) =
+            match self {
+                Instructions::Data { data, .. } => (data.to_string(), String::new()),
+                Instructions::JumpIf { carry, a_larger, equal, zero, .. }
+                | Instructions::JumpIfNot { carry, a_larger, equal, zero, .. } => {
+                    (String::new(), flag_string(*carry, *a_larger, *equal, *zero))
+                }
+                _ => (String::new(), String::new()),
+            };
+
+        template
+            .replace("{a}", &a)
+            .replace("{b}", &b)
+            .replace("{reg}", &reg)
+            .replace("{data}", &data)
+            .replace("{mark}", self.referenced_mark().unwrap_or_default())
+            .replace("{flags}", &flags)
+            .replace("{mnemonic}", self.mnemonic())
+    }
+
+    //Number of RAM cells the instruction takes up, including the address of its mark.
+    pub fn size(&self, word_bits: usize) -> usize {
+        let mark_cells = self.jump_mark().map_or(0, |_| 1);
+        Instructions::binary(self.clone(), word_bits).lines().count() + mark_cells
+    }
+
+    //The mark the instruction jumps to, if any.
+    pub fn jump_mark(&self) -> Option<&str> {
+        match self {
+            Instructions::JumpAddress { mark }
+            | Instructions::JumpIf { mark, .. }
+            | Instructions::JumpIfNot { mark, .. } => Some(mark),
+            Instructions::Custom { operands, .. } => {
+                match operands.last() {
+                    Some(CustomOperand::Mark(mark)) => Some(mark),
+                    _ => None
+                }
+            }
+            _ => None
+        }
+    }
+
+    //The mark the instruction jumps to or otherwise needs the address of.
+    pub fn referenced_mark(&self) -> Option<&str> {
+        match self {
+            Instructions::PcRelative { mark, .. } => Some(mark),
+            _ => self.jump_mark(),
+        }
+    }
+
+    pub fn referenced_mark_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Instructions::JumpAddress { mark }
+            | Instructions::JumpIf { mark, .. }
+            | Instructions::JumpIfNot { mark, .. }
+            | Instructions::PcRelative { mark, .. } => Some(mark),
+            Instructions::Custom { operands, .. } => {
+                match operands.last_mut() {
+                    Some(CustomOperand::Mark(mark)) => Some(mark),
+                    _ => None
+                }
+            }
+            _ => None
+        }
+    }
+
+    //Leading bits of a built in instruction from INSTRUCTION_SET. NOP, .breakpoint and DBG are
+    //written with the opcode in the config instead.
+    pub fn opcode(&self) -> &'static str {
+        opcode(self.mnemonic())
+    }
+
+    pub fn binary(instruction: Self, word_bits: usize) -> String {
+        let binary_string =
+            match instruction {
+                Instructions::Add { ref reg_a, ref reg_b }
+                | Instructions::Shr { ref reg_a, ref reg_b }
+                | Instructions::Shl { ref reg_a, ref reg_b }
+                | Instructions::Not { ref reg_a, ref reg_b }
+                | Instructions::And { ref reg_a, ref reg_b }
+                | Instructions::Or { ref reg_a, ref reg_b }
+                | Instructions::XOr { ref reg_a, ref reg_b }
+                | Instructions::Store { ref reg_a, ref reg_b }
+                | Instructions::Load { ref reg_a, ref reg_b } => {
+                    format!("{}{}{}", instruction.opcode(), Register::binary(reg_a.clone()), Register::binary(reg_b.clone()))
+                }
+                Instructions::Data { reg, data } => {
+                    format!("{}{}\n{}", opcode("DATA"), Register::binary(reg), data_binary(data, word_bits))
+                }
+                //The distance is not known until every address is.
+                Instructions::PcRelative { reg, .. } => {
+                    format!("{}{}\n{}", opcode("DATA"), Register::binary(reg), data_binary(0, word_bits))
+                }
+                Instructions::JumpRegister { reg } => {
+                    format!("{}{}", opcode("JMPR"), Register::binary(reg))
+                }
+                Instructions::JumpAddress { .. } => {
+                    opcode("JMP").to_string()
+                }
+                Instructions::JumpIf { carry, a_larger, equal, zero, .. } => {
+                    format!(
+                        "{}{}{}{}{}",
+                        opcode("JIF"),
+                        bool_char(carry),
+                        bool_char(a_larger),
+                        bool_char(equal),
+                        bool_char(zero),
+                    )
+                }
+                Instructions::JumpIfNot { carry, a_larger, equal, zero, .. } => {
+                    format!(
+                        "{}{}{}{}{}",
+                        opcode("JIFN"),
+                        bool_char(!carry),
+                        bool_char(!a_larger),
+                        bool_char(!equal),
+                        bool_char(!zero),
+                    )
+                }
+                Instructions::ClearFlags => {
+                    opcode("CLF").to_string()
+                }
+                Instructions::End => opcode("END").to_string(),
+                Instructions::Nop { opcode } | Instructions::Breakpoint { opcode } => format!("{:08b}", opcode),
+                Instructions::Debug { opcode, reg } => {
+                    format!("{:06b}{}", opcode >> REGISTER_BITS, Register::binary(reg))
+                }
+                Instructions::Custom { opcode, operands, .. } => {
+                    let mut binary_string = opcode;
+                    let mut imm = None;
+                    for operand in operands {
+                        match operand {
+                            CustomOperand::Reg(reg) => binary_string.push_str(Register::binary(reg)),
+                            CustomOperand::Flags { carry, a_larger, equal, zero } => {
+                                binary_string.extend([bool_char(carry), bool_char(a_larger), bool_char(equal), bool_char(zero)]);
+                            }
+                            CustomOperand::Imm(data) => imm = Some(data_binary(data, word_bits)),
+                            //Resolved by the assembler once all marks are known.
+                            CustomOperand::Mark(_) => {}
+                        }
+                    }
+
+                    while binary_string.len() < word_bits {
+                        binary_string.push('0');
+                    }
+
+                    if let Some(imm) = imm {
+                        binary_string.push('\n');
+                        binary_string.push_str(&imm);
+                    }
+
+                    binary_string
+                }
+            };
+
+        binary_string
+    }
+}
+
+//Writes the instruction back out as assembly source.
+impl fmt::Display for Instructions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instructions::Add { reg_a, reg_b } => write!(f, "ADD {} {}", reg_a, reg_b),
+            Instructions::Shr { reg_a, reg_b } => write!(f, "SHR {} {}", reg_a, reg_b),
+            Instructions::Shl { reg_a, reg_b } => write!(f, "SHL {} {}", reg_a, reg_b),
+            Instructions::Not { reg_a, reg_b } => write!(f, "NOT {} {}", reg_a, reg_b),
+            Instructions::And { reg_a, reg_b } => write!(f, "AND {} {}", reg_a, reg_b),
+            Instructions::Or { reg_a, reg_b } => write!(f, "OR {} {}", reg_a, reg_b),
+            Instructions::XOr { reg_a, reg_b } => write!(f, "XOR {} {}", reg_a, reg_b),
+            Instructions::Store { reg_a, reg_b } => write!(f, "ST {} {}", reg_a, reg_b),
+            Instructions::Load { reg_a, reg_b } => write!(f, "LD {} {}", reg_a, reg_b),
+            Instructions::Data { reg, data } => write!(f, "DATA {} {}", reg, data),
+            Instructions::JumpRegister { reg } => write!(f, "JMPR {}", reg),
+            Instructions::JumpAddress { mark } => write!(f, "JMP {}", mark),
+            Instructions::JumpIf { carry, a_larger, equal, zero, mark } => {
+                write!(f, "JIF {} {}", flag_string(*carry, *a_larger, *equal, *zero), mark)
+            }
+            Instructions::JumpIfNot { carry, a_larger, equal, zero, mark } => {
+                write!(f, "JIFN {} {}", flag_string(*carry, *a_larger, *equal, *zero), mark)
+            }
+            Instructions::ClearFlags => write!(f, "CLF"),
+            Instructions::Nop { .. } => write!(f, "NOP"),
+            Instructions::Breakpoint { .. } => write!(f, ".breakpoint"),
+            Instructions::Debug { reg, .. } => write!(f, "DBG {}", reg),
+            Instructions::PcRelative { reg, mark } => write!(f, "PCREL {} {}", reg, mark),
+            Instructions::End => write!(f, "END"),
+            Instructions::Custom { mnemonic, operands, .. } => {
+                write!(f, "{}", mnemonic)?;
+                for operand in operands {
+                    write!(f, " {}", operand)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//Instruction sets loaded from a TOML file with --isa, replacing the built in instructions.
+//
+//    [[instructions]]
+//    mnemonic = "ADD"
+//    opcode = "1000"
+//    operands = ["REG", "REG"]
+//
+//REG and FLAGS operands are appended to the opcode bits, which must fit in a RAM cell and are
+//then padded with zeros to fill it. An IMM or MARK operand takes up the following RAM cell, so it
+//must be last.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::config::{ConfigError, REGISTER_BITS};
+use crate::instructions::Operand;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InstructionDef {
+    pub mnemonic: String,
+    pub opcode: String,
+    #[serde(default)]
+    pub operands: Vec<Operand>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Isa {
+    pub instructions: Vec<InstructionDef>,
+}
+
+impl Isa {
+    //Each instruction must fit its opcode and REG and FLAGS operands into a RAM cell of word_bits.
+    pub fn load(path: &Path, word_bits: usize) -> Result<Isa, ConfigError> {
+        let content = fs::read_to_string(path).map_err(|error|
+            ConfigError::Io { path: path.to_path_buf(), error }
+        )?;
+
+        let isa: Isa = toml::from_str(&content).map_err(|error|
+            ConfigError::Parse { path: path.to_path_buf(), error }
+        )?;
+
+        isa.validate(path, word_bits)?;
+
+        Ok(isa)
+    }
+
+    fn validate(&self, path: &Path, word_bits: usize) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidIsa { path: PathBuf::from(path), reason };
+
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            if instruction.opcode.is_empty() || !instruction.opcode.chars().all(|c| c == '0' || c == '1') {
+                return Err(invalid(format!("opcode {} of {} is not a binary string", instruction.opcode, instruction.mnemonic)));
+            }
+
+            if instruction.mnemonic == "MARK" || self.instructions[..i].iter().any(|other| other.mnemonic == instruction.mnemonic) {
+                return Err(invalid(format!("mnemonic {} is already defined", instruction.mnemonic)));
+            }
+
+            let extra_cells = instruction.operands.iter()
+                .position(|operand| *operand == Operand::Imm || *operand == Operand::Mark);
+
+            if let Some(position) = extra_cells {
+                if position != instruction.operands.len() - 1 {
+                    return Err(invalid(format!("the IMM or MARK operand of {} must be the last operand", instruction.mnemonic)));
+                }
+            }
+
+            let bits = instruction.opcode.len() + instruction.operands.iter()
+                .map(|operand| match operand {
+                    Operand::Reg => REGISTER_BITS,
+                    Operand::Flags => 4,
+                    Operand::Imm | Operand::Mark => 0,
+                })
+                .sum::<usize>();
+
+            if bits > word_bits {
+                return Err(invalid(format!("{} takes {} bits, more than the {} bits of a RAM cell", instruction.mnemonic, bits, word_bits)));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn find(&self, mnemonic: &str) -> Option<&InstructionDef> {
+        self.instructions.iter().find(|instruction| instruction.mnemonic == mnemonic)
+    }
+}
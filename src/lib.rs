@@ -0,0 +1,72 @@
+pub mod analysis;
+pub mod assembler;
+pub mod ast;
+pub mod batch;
+pub mod build_helper;
+pub mod cache;
+pub mod checksum;
+pub mod config;
+pub mod control_flow;
+pub mod coverage;
+pub mod directive;
+pub mod disassembler;
+pub mod editor;
+pub mod error;
+pub mod feature;
+pub mod file_system;
+pub mod flow;
+pub mod fmt;
+pub mod info;
+pub mod instructions;
+pub mod isa;
+pub mod macros;
+pub mod mark_name;
+pub mod optimizer;
+pub mod output;
+pub mod patch;
+pub mod pseudo;
+pub mod self_test;
+pub mod stats;
+pub mod test_harness;
+pub mod timing;
+pub mod warning;
+
+pub use assembler::{assemble_str, AssembledInstruction, AssembledProgram};
+pub use error::AssemblerError;
+pub use warning::Warning;
+
+use instructions::Register;
+
+//The flags tested by JIF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CpuFlag {
+    Carry,
+    ALarger,
+    Equal,
+    Zero,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepOutcome {
+    //The instruction ran and the next one can be stepped.
+    Running,
+    //END ran.
+    Halted,
+    //The byte at the address is not an instruction the simulator knows.
+    InvalidInstruction { address: u8 },
+}
+
+//What a simulator of the CPU provides so the test harness can run assembled programs on it.
+pub trait SimulatorInterface {
+    //Replaces the RAM with the bytes of a program, starting at address 0, and restarts the CPU.
+    fn load_binary(&mut self, binary: &[u8]);
+
+    fn get_register(&self, reg: Register) -> u8;
+
+    fn get_flag(&self, flag: CpuFlag) -> bool;
+
+    //Runs the instruction at the program counter.
+    fn step(&mut self) -> StepOutcome;
+
+    fn program_counter(&self) -> u8;
+}
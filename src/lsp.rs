@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, LanguageServer, LspService, Server};
+
+use logical_cpu_assembler::{assemble_str, AssemblerError};
+use logical_cpu_assembler::config::Config;
+use logical_cpu_assembler::editor::{self, completions, find_references, hover, mark_definition, mark_tokens, word_at, CompletionKind, Span};
+
+struct Backend {
+    client: Client,
+    config: Config,
+    //Text of every open document, replaced whole on each change.
+    documents: Mutex<HashMap<Url, String>>,
+}
+
+//LSP positions count UTF-16 code units from the start of the line.
+fn offset_to_position(source: &str, offset: usize) -> Position {
+    let line_start = source[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+
+    Position {
+        line: source[..offset].matches('\n').count() as u32,
+        character: source[line_start..offset].encode_utf16().count() as u32,
+    }
+}
+
+fn position_to_offset(source: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for line in source.split_inclusive('\n').take(position.line as usize) {
+        line_start += line.len();
+    }
+
+    let mut character = 0;
+    for (i, c) in source[line_start..].char_indices() {
+        if character >= position.character as usize || c == '\n' {
+            return line_start + i;
+        }
+        character += c.len_utf16();
+    }
+
+    source.len()
+}
+
+fn span_to_range(source: &str, span: Span) -> Range {
+    Range { start: offset_to_position(source, span.start), end: offset_to_position(source, span.end) }
+}
+
+//The whole of a source line, counting from 1.
+fn line_range(source: &str, line: usize) -> Range {
+    let text = source.lines().nth(line.saturating_sub(1)).unwrap_or_default();
+    let line = line.saturating_sub(1) as u32;
+
+    Range {
+        start: Position { line, character: 0 },
+        end: Position { line, character: text.encode_utf16().count() as u32 },
+    }
+}
+
+//Messages start with the line number, which the editor already shows.
+fn without_line_number(message: String, has_line: bool) -> String {
+    match message.split_once(' ') {
+        Some((_, rest)) if has_line => rest.to_string(),
+        _ => message,
+    }
+}
+
+fn diagnostics(source: &str, config: &Config) -> Vec<Diagnostic> {
+    match assemble_str(source, config) {
+        Ok(program) => {
+            program.warnings.iter()
+                .map(|warning| Diagnostic {
+                    range: line_range(source, warning.line()),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    message: without_line_number(warning.to_string(), true),
+                    ..Diagnostic::default()
+                })
+                .collect()
+        }
+        Err(error) => {
+            let missing_mark =
+                match &error {
+                    AssemblerError::MarkNotFound { mark } => {
+                        mark_tokens(source).into_iter().find(|token| token.name == mark)
+                    }
+                    _ => None,
+                };
+
+            let range =
+                match (error.line(), missing_mark) {
+                    (Some(line), _) => line_range(source, line),
+                    (None, Some(token)) => span_to_range(source, token.span),
+                    (None, None) => line_range(source, 1),
+                };
+
+            vec![Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(error.code().to_string())),
+                message: without_line_number(error.message(), error.line().is_some()),
+                ..Diagnostic::default()
+            }]
+        }
+    }
+}
+
+impl Backend {
+    fn document(&self, uri: &Url) -> Option<String> {
+        self.documents.lock().unwrap().get(uri).cloned()
+    }
+
+    async fn update(&self, uri: Url, text: String) {
+        let diagnostics = diagnostics(&text, &self.config);
+        self.documents.lock().unwrap().insert(uri.clone(), text);
+        self.client.publish_diagnostics(uri, diagnostics, None).await;
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+                definition_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                completion_provider: Some(CompletionOptions::default()),
+                ..ServerCapabilities::default()
+            },
+            server_info: Some(ServerInfo {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        self.update(params.text_document.uri, params.text_document.text).await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        //Only full syncs are asked for, so the last change is the whole document.
+        if let Some(change) = params.content_changes.into_iter().last() {
+            self.update(params.text_document.uri, change.text).await;
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        self.documents.lock().unwrap().remove(&params.text_document.uri);
+        self.client.publish_diagnostics(params.text_document.uri, Vec::new(), None).await;
+    }
+
+    async fn goto_definition(&self, params: GotoDefinitionParams) -> Result<Option<GotoDefinitionResponse>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let source =
+            match self.document(&uri) {
+                Some(source) => source,
+                None => return Ok(None),
+            };
+
+        let offset = position_to_offset(&source, params.text_document_position_params.position);
+
+        Ok(mark_definition(&source, offset).map(|span| {
+            GotoDefinitionResponse::Scalar(Location { uri, range: span_to_range(&source, span) })
+        }))
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = params.text_document_position.text_document.uri;
+        let source =
+            match self.document(&uri) {
+                Some(source) => source,
+                None => return Ok(None),
+            };
+
+        let offset = position_to_offset(&source, params.text_document_position.position);
+        let name =
+            match word_at(&source, offset) {
+                Some((name, _)) => name,
+                None => return Ok(None),
+            };
+
+        let definition = mark_definition(&source, offset);
+        let locations = find_references(&source, name).into_iter()
+            .filter(|span| params.context.include_declaration || Some(*span) != definition)
+            .map(|span| Location { uri: uri.clone(), range: span_to_range(&source, span) })
+            .collect();
+
+        Ok(Some(locations))
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let source = self.document(&params.text_document_position_params.text_document.uri).unwrap_or_default();
+        let offset = position_to_offset(&source, params.text_document_position_params.position);
+
+        Ok(hover(&source, offset).map(|info| {
+            let mut value = format!("**{}**\n\n{}", info.title, info.description);
+            if let Some(encoding) = info.encoding {
+                value.push_str(&format!("\n\n`{}`", encoding));
+            }
+
+            Hover {
+                contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+                range: word_at(&source, offset).map(|(_, span)| span_to_range(&source, span)),
+            }
+        }))
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let source = self.document(&params.text_document.uri).unwrap_or_default();
+
+        //Addresses are only known when the whole program assembles.
+        let marks: HashMap<String, usize> =
+            match assemble_str(&source, &self.config) {
+                Ok(program) => program.marks.into_iter().collect(),
+                Err(_) => return Ok(None),
+            };
+
+        let hints = editor::inlay_hints(&source, &marks).into_iter()
+            .map(|hint| {
+                let kind =
+                    match hint.kind {
+                        editor::InlayHintKind::MarkAddress => InlayHintKind::TYPE,
+                        editor::InlayHintKind::JumpTarget => InlayHintKind::PARAMETER,
+                    };
+
+                InlayHint {
+                    position: offset_to_position(&source, hint.position),
+                    label: InlayHintLabel::String(hint.label),
+                    kind: Some(kind),
+                    text_edits: None,
+                    tooltip: None,
+                    padding_left: Some(true),
+                    padding_right: None,
+                    data: None,
+                }
+            })
+            .collect();
+
+        Ok(Some(hints))
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let source = self.document(&params.text_document_position.text_document.uri).unwrap_or_default();
+        let offset = position_to_offset(&source, params.text_document_position.position);
+
+        let items = completions(&source, offset).into_iter()
+            .map(|completion| {
+                let kind =
+                    match completion.kind {
+                        CompletionKind::Instruction => CompletionItemKind::KEYWORD,
+                        CompletionKind::Register => CompletionItemKind::VARIABLE,
+                        CompletionKind::Mark => CompletionItemKind::REFERENCE,
+                        CompletionKind::Directive => CompletionItemKind::KEYWORD,
+                        CompletionKind::Constant => CompletionItemKind::CONSTANT,
+                    };
+
+                CompletionItem {
+                    label: completion.text,
+                    kind: Some(kind),
+                    detail: Some(completion.detail),
+                    ..CompletionItem::default()
+                }
+            })
+            .collect();
+
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+}
+
+//Serves the language server protocol over stdin and stdout until the editor exits.
+pub fn run(config: &Config) -> std::io::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+
+    runtime.block_on(async {
+        let (service, socket) = LspService::new(|client| Backend {
+            client,
+            config: config.clone(),
+            documents: Mutex::new(HashMap::new()),
+        });
+
+        Server::new(tokio::io::stdin(), tokio::io::stdout(), socket).serve(service).await;
+    });
+
+    Ok(())
+}
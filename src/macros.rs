@@ -0,0 +1,46 @@
+//Macros defined with .macro and used like an instruction.
+//
+//    .macro SWAP a b
+//    XOR \a \b
+//    XOR \b \a
+//    XOR \a \b
+//    .endm
+//
+//    SWAP R0 R1
+//
+//In the body \name is replaced by the argument given for the parameter name, \@ by a number that
+//is different for every use of a macro so marks in the body are unique, and \scratch by the
+//scratch register. The standard library in std.asm is included with .include <std>.
+
+use crate::instructions::Register;
+
+pub const STD_LIBRARY: &str = include_str!("std.asm");
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Macro {
+    pub params: Vec<String>,
+    pub body: Vec<String>,
+    //Source line of the .macro directive.
+    pub line: usize,
+}
+
+impl Macro {
+    //The lines of the body with the arguments put in, there must be one for each parameter.
+    pub fn expand(&self, args: &[&str], id: usize, scratch: &Register) -> Vec<String> {
+        //Longest first so a parameter that starts with the name of another is replaced whole.
+        let mut substitutions: Vec<(String, String)> = self.params.iter()
+            .zip(args)
+            .map(|(param, arg)| (format!("\\{}", param), arg.to_string()))
+            .collect();
+        substitutions.push(("\\@".to_string(), id.to_string()));
+        substitutions.push(("\\scratch".to_string(), scratch.to_string()));
+        substitutions.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+        self.body.iter()
+            .map(|line| {
+                substitutions.iter()
+                    .fold(line.clone(), |line, (name, value)| line.replace(name.as_str(), value))
+            })
+            .collect()
+    }
+}
@@ -1,365 +1,955 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{Write, Read};
-
-const NUMBER_BITS: usize = 8;
-
-#[derive(Clone, Debug)]
-pub enum Register {
-    R0,
-    R1,
-    R2,
-    R3,
+mod lsp;
+mod repl;
+
+use std::fmt::Display;
+use std::fs;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Mutex;
+
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use indexmap::IndexMap;
+use log::{error, info, warn, Level};
+use rayon::prelude::*;
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::prelude::*;
+
+use logical_cpu_assembler::batch::{parse_manifest, run_batch, BatchSummary};
+use logical_cpu_assembler::cache::{self, CacheEntry};
+use logical_cpu_assembler::checksum::verify_crc16;
+use logical_cpu_assembler::config::{self, Config, PartialConfig};
+use logical_cpu_assembler::coverage::{parse_trace, Coverage};
+use logical_cpu_assembler::disassembler::{disassemble, parse_hex, write_source};
+use logical_cpu_assembler::error::{explanation, EXPLANATIONS};
+use logical_cpu_assembler::fmt::{FormatConfig, Formatter};
+use logical_cpu_assembler::info;
+use logical_cpu_assembler::assembler::{parse_line, source_hash, Assembler, ParseContext};
+use logical_cpu_assembler::instructions::{Instructions, Register};
+use logical_cpu_assembler::isa::Isa;
+use logical_cpu_assembler::output::bin::{from_bytes, BinaryHeader};
+use logical_cpu_assembler::output::coe::CoeRadix;
+use logical_cpu_assembler::output::dap::DapSourceMapWriter;
+use logical_cpu_assembler::output::line_ending::{LineEnding, LineEndingWriter};
+use logical_cpu_assembler::output::listing::ListingWriter;
+use logical_cpu_assembler::output::map::MapWriter;
+use logical_cpu_assembler::output::stats::StatsWriter;
+use logical_cpu_assembler::output::svg::SvgWriter;
+use logical_cpu_assembler::output::symbols::{parse_symbols, SymbolsWriter};
+use logical_cpu_assembler::output::xref::XRefTableWriter;
+use logical_cpu_assembler::output::{atomic_write, OutputFormat, OutputTarget, OutputWriter};
+use logical_cpu_assembler::patch::{apply_patch, create_patch};
+use logical_cpu_assembler::self_test::{run_self_test, SelfTestResult, SELF_TESTS};
+use logical_cpu_assembler::stats::{instruction_histogram, percentage, write_histogram, ComplexityReport};
+use logical_cpu_assembler::timing::TimingModel;
+use logical_cpu_assembler::warning::WarnKind;
+use logical_cpu_assembler::{assemble_str, AssembledProgram, AssemblerError};
+
+const ENV_HELP: &str = "\
+Options are read from the command line first, then from these environment \
+variables and finally from the nearest .assembler.toml file:
+  LCPU_FORMAT        Same as --format
+  LCPU_ADDRESS_BITS  Same as --address-bits
+  LCPU_WORD_BITS     Same as --word-bits
+  LCPU_STRICT        Same as --strict (true/false)
+  LCPU_SCRATCH_REG   Same as --scratch-reg";
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Table,
+    Json,
 }
 
-impl Register {
-    fn binary(reg: Register) -> &'static str {
-        match reg {
-            Register::R0 => "00",
-            Register::R1 => "01",
-            Register::R2 => "10",
-            Register::R3 => "11",
-        }
-    }
+#[derive(Clone, Copy, ValueEnum)]
+enum VizFormat {
+    Svg,
+}
 
-    fn reg_from_instr(reg: &str, real_line_number: usize) -> Register {
-        match reg {
-            "R0" => Register::R0,
-            "R1" => Register::R1,
-            "R2" => Register::R2,
-            "R3" => Register::R3,
-            _ => panic!("{} Invalid register number found of {}.", real_line_number, reg)
-        }
-    }
+#[derive(Subcommand)]
+enum Command {
+    /// List the supported instructions, registers, directives and output formats.
+    Info {
+        #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+        format: ReportFormat,
+    },
+    /// List the registers with their encoding and the aliases a program gives them with .alias.
+    Registers {
+        /// Program whose .alias directives are listed.
+        program: Option<PathBuf>,
+
+        #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+        format: ReportFormat,
+    },
+    /// List every instruction with its opcode, operands and description, and what each pseudo instruction expands into.
+    Opcodes {
+        #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+        format: ReportFormat,
+    },
+    /// Explain an error code, such as E010, with its common causes and how to fix it.
+    Explain {
+        code: String,
+    },
+    /// Print the grammar of the assembly language in EBNF.
+    Grammar,
+    /// Convert machine code in the ms format back into assembly source.
+    Disassemble {
+        /// Machine code file.
+        #[arg(required_unless_present = "hex", conflicts_with = "hex")]
+        input: Option<PathBuf>,
+
+        /// Machine code as hex instead of a file, each pair of digits is one RAM cell.
+        #[arg(long, value_name = "HEX")]
+        hex: Option<String>,
+
+        /// Text between the groups of digits given with --hex, empty for none.
+        #[arg(long, value_name = "SEPARATOR", default_value = " ", requires = "hex")]
+        hex_separator: String,
+
+        /// Symbol file written with --symbols, used to name jump targets.
+        #[arg(long)]
+        symbols: Option<PathBuf>,
+    },
+    /// Write the instructions of a patch file over machine code in the ms format.
+    Patch {
+        /// Machine code file to patch.
+        #[arg(long)]
+        input: PathBuf,
+
+        /// Assembly source placed with .org ADDRESS lines.
+        #[arg(long)]
+        patch: PathBuf,
+
+        /// File the patched machine code is written to.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Write a patch file to stdout that turns one ms file into another.
+    CreatePatch {
+        /// Machine code the patch is applied to.
+        original: PathBuf,
+
+        /// Machine code the patch produces.
+        modified: PathBuf,
+    },
+    /// Decode machine code and report how it uses the instruction set.
+    #[command(group(ArgGroup::new("report").required(true).multiple(true).args(["histogram", "complexity"])))]
+    Analyze {
+        /// Machine code file.
+        input: PathBuf,
+
+        /// Count how many times each instruction is used.
+        #[arg(long)]
+        histogram: bool,
+
+        /// Report the cyclomatic complexity of the program along with its branches, instructions and jump targets.
+        #[arg(long)]
+        complexity: bool,
+
+        #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+        format: ReportFormat,
+    },
+    /// Report which marks and instructions of a program ran, from a trace of executed addresses.
+    Coverage {
+        /// File with one executed address per line, in decimal or hex starting with 0x.
+        #[arg(long)]
+        trace: PathBuf,
+
+        /// Assembly source file.
+        program: PathBuf,
+
+        /// File the coverage listing is written to, defaults to the source path with .cov added.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Draw the RAM used by a program as a grid of cells.
+    Viz {
+        #[arg(long, value_enum, default_value_t = VizFormat::Svg)]
+        format: VizFormat,
+
+        /// Assembly source file.
+        program: PathBuf,
+
+        /// Output file, defaults to the source path with .svg added.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Number of RAM cells drawn, defaults to the whole address space.
+        #[arg(long)]
+        size: Option<usize>,
+
+        /// Addresses used for memory mapped IO, drawn in red. Can be given more than once.
+        #[arg(long, value_name = "START-END", value_parser = parse_address_range)]
+        mmio: Vec<RangeInclusive<usize>>,
+    },
+    /// Run a language server over stdin and stdout for editor integration.
+    Lsp,
+    /// Reformat assembly source in a consistent style and write it to stdout.
+    Fmt {
+        /// Assembly source file.
+        input: PathBuf,
+
+        /// Overwrite the file instead of writing to stdout.
+        #[arg(long)]
+        in_place: bool,
+    },
+    /// Assemble every "INPUT OUTPUT" pair listed in a manifest file, one pair per line.
+    Batch {
+        /// Manifest file.
+        manifest: PathBuf,
+
+        /// Assemble the files at the same time.
+        #[arg(long)]
+        parallel: bool,
+
+        /// Print the time taken by each file.
+        #[arg(long)]
+        stats: bool,
+    },
+    /// Check that machine code in the binary-v2 format was assembled from a source file.
+    VerifySource {
+        /// Machine code file in the binary-v2 format.
+        program: PathBuf,
+
+        /// Assembly source file.
+        source: PathBuf,
+    },
+    /// Check the CRC-16 added by --append-crc16 to the last two RAM cells of a program.
+    VerifyCrc16 {
+        /// Machine code file in the ms or binary-v2 format.
+        program: PathBuf,
+    },
+    /// Assemble the programs built into the assembler and compare them with known good machine code.
+    SelfTest,
 }
 
-#[allow(dead_code)]
-pub enum Instructions {
-    Add { reg_a: Register, reg_b: Register },
-    Shr { reg_a: Register, reg_b: Register },
-    Shl { reg_a: Register, reg_b: Register },
-    Not { reg_a: Register, reg_b: Register },
-    And { reg_a: Register, reg_b: Register },
-    Or { reg_a: Register, reg_b: Register },
-    XOr { reg_a: Register, reg_b: Register },
-    Store { reg_a: Register, reg_b: Register },
-    Load { reg_a: Register, reg_b: Register },
-    Data { reg: Register, data: usize },
-    JumpRegister { reg: Register },
-    JumpAddress { mark: String },
-    JumpIf { carry: bool, a_larger: bool, equal: bool, zero: bool, mark: String },
-    ClearFlags,
-    End,
+#[derive(Parser)]
+#[command(version, about = "Assembler for the logical CPU.", after_help = ENV_HELP)]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Assembly source file.
+    #[arg(required_unless_present = "repl")]
+    input: Option<PathBuf>,
+
+    /// Output file, defaults to machine_code/<input name>.<format extension>.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Output file format [default: ms].
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Radix used for the values of the coe format.
+    #[arg(long, value_enum, default_value_t = CoeRadix::Binary)]
+    coe_radix: CoeRadix,
+
+    /// Number of bits in a RAM address [default: 8].
+    #[arg(long)]
+    address_bits: Option<usize>,
+
+    /// Number of bits in a RAM cell [default: 8].
+    #[arg(long)]
+    word_bits: Option<usize>,
+
+    /// Register used as a temporary by instructions that expand into several others [default: R3].
+    #[arg(long, value_enum)]
+    scratch_reg: Option<Register>,
+
+    /// Characters that start a comment line, such as ; or //. Repeat or separate with commas for several [default: #].
+    #[arg(long, value_delimiter = ',')]
+    comment_chars: Option<Vec<String>>,
+
+    /// Instruction set definition to use instead of the built in instructions.
+    #[arg(long)]
+    isa: Option<PathBuf>,
+
+    /// TOML file giving the cycles taken by each instruction, used for the estimated cycles and --profile-annotate.
+    #[arg(long, value_name = "FILE")]
+    timing_model: Option<PathBuf>,
+
+    /// Write a cross reference table of marks and registers to FILE, or stdout when no FILE is given.
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+    xref: Option<PathBuf>,
+
+    /// Write a listing of the address, RAM cells and instruction of each instruction to FILE, or stdout when no FILE is given.
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+    listing: Option<PathBuf>,
+
+    /// End each line of the listing with a plain English description of the instruction.
+    #[arg(long, requires = "listing")]
+    explain: bool,
+
+    /// Show the cycles taken by each instruction of the listing, from --timing-model.
+    #[arg(long, requires = "listing")]
+    profile_annotate: bool,
+
+    /// Write a Debug Adapter Protocol source map from RAM addresses to source lines to FILE.
+    #[arg(long, value_name = "FILE")]
+    dap_source_map: Option<PathBuf>,
+
+    /// Write the address of each mark to FILE.
+    #[arg(long, value_name = "FILE")]
+    symbols: Option<PathBuf>,
+
+    /// Write a map of the entry point, size, marks and modules of the program for ROM tools to FILE.
+    #[arg(long, value_name = "FILE")]
+    export_map: Option<PathBuf>,
+
+    /// Write the output files one after another instead of at the same time.
+    #[arg(long)]
+    no_parallel_output: bool,
+
+    /// Place a NOP after every JMP, JIF and JMPR that is not followed by a non branching instruction.
+    #[arg(long)]
+    fill_delay_slots: bool,
+
+    /// Instruction placed by --fill-delay-slots.
+    #[arg(long, value_name = "INSTRUCTION", default_value = "NOP")]
+    delay_slot_instruction: String,
+
+    /// Opcode written for NOP, in binary with 0b, hex with 0x or decimal [default: 0b01100000, CLF].
+    #[arg(long, value_name = "OPCODE", value_parser = parse_opcode)]
+    nop_opcode: Option<u8>,
+
+    /// Fail when the program uses more than N RAM cells.
+    #[arg(long, value_name = "N")]
+    max_size: Option<usize>,
+
+    /// Print how much of the RAM and the size budget the program uses.
+    #[arg(long)]
+    stats: bool,
+
+    /// Write the size and instruction counts of the program to FILE as JSON.
+    #[arg(long, value_name = "FILE")]
+    stats_output: Option<PathBuf>,
+
+    /// Skip assembling when the inputs, options and outputs are the same as the last run.
+    #[arg(long)]
+    cache: bool,
+
+    /// Directory the --cache file is kept in.
+    #[arg(long, value_name = "PATH", default_value = ".")]
+    cache_dir: PathBuf,
+
+    /// Print each step of the assembly process to stderr, the same as RUST_LOG=logical_cpu_assembler=trace.
+    #[arg(long)]
+    trace: bool,
+
+    /// Record how long each step of the assembler takes to FILE in the Chrome trace format, trace.json when no FILE is given.
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "trace.json")]
+    profile: Option<PathBuf>,
+
+    /// Opcode written by .breakpoint, as a decimal, 0x hex or 0b binary number [default: 0xFF].
+    #[arg(long, value_parser = parse_opcode)]
+    breakpoint_opcode: Option<u8>,
+
+    /// Warn when more .breakpoint directives than this are used.
+    #[arg(long, default_value_t = config::DEFAULT_MAX_BREAKPOINTS)]
+    max_breakpoints: usize,
+
+    /// Opcode written by DBG, the low two bits are left for the register [default: 0b11110000].
+    #[arg(long, value_parser = parse_opcode)]
+    debug_opcode: Option<u8>,
+
+    /// Write .breakpoint as a NOP and leave out DBG.
+    #[arg(long)]
+    release: bool,
+
+    /// Add the CRC-16/CCITT of the program as its last two RAM cells, high byte first.
+    #[arg(long)]
+    append_crc16: bool,
+
+    /// Address the program is loaded at, added to the address of every jump, as a decimal or 0x hex number.
+    #[arg(long, value_name = "N", value_parser = parse_address, default_value_t = 0)]
+    base_address: usize,
+
+    /// Line ending of text outputs, binary outputs are written as they are.
+    #[arg(long, value_enum, default_value_t = LineEnding::Lf)]
+    line_ending: LineEnding,
+
+    /// Assemble the .feature blocks named FLAG_NAME, repeat for several.
+    #[arg(long = "feature", value_name = "FLAG_NAME")]
+    features: Vec<String>,
+
+    /// Start an interactive session that encodes instructions as they are entered.
+    #[arg(long)]
+    repl: bool,
+
+    /// Do not warn when the program does not finish with END, JMP or JMPR.
+    #[arg(long)]
+    no_warn_no_terminal: bool,
+
+    /// Do not warn about a CLF when the flags are already clear.
+    #[arg(long)]
+    no_warn_redundant_clf: bool,
+
+    /// Do not warn about a JIF that only tests flags cleared by a CLF.
+    #[arg(long)]
+    no_warn_always_false_jif: bool,
+
+    /// Do not warn when a DATA value is truncated to fit in a RAM cell.
+    #[arg(long)]
+    no_warn_data_truncated: bool,
+
+    /// Do not warn about a MARK that nothing jumps to.
+    #[arg(long)]
+    no_warn_unused_mark: bool,
+
+    /// Treat DATA values that do not fit in a RAM cell as errors instead of truncating them.
+    #[arg(long)]
+    strict: bool,
 }
 
-impl Instructions {
-    fn binary(instruction: Self) -> String {
-        let binary_string =
-            match instruction {
-                Instructions::Add { reg_a, reg_b } => {
-                    format!("1000{}{}", Register::binary(reg_a), Register::binary(reg_b))
-                }
-                Instructions::Shr { reg_a, reg_b } => {
-                    format!("1001{}{}", Register::binary(reg_a), Register::binary(reg_b))
-                }
-                Instructions::Shl { reg_a, reg_b } => {
-                    format!("1010{}{}", Register::binary(reg_a), Register::binary(reg_b))
-                }
-                Instructions::Not { reg_a, reg_b } => {
-                    format!("1011{}{}", Register::binary(reg_a), Register::binary(reg_b))
-                }
-                Instructions::And { reg_a, reg_b } => {
-                    format!("1100{}{}", Register::binary(reg_a), Register::binary(reg_b))
-                }
-                Instructions::Or { reg_a, reg_b } => {
-                    format!("1101{}{}", Register::binary(reg_a), Register::binary(reg_b))
-                }
-                Instructions::XOr { reg_a, reg_b } => {
-                    format!("1110{}{}", Register::binary(reg_a), Register::binary(reg_b))
-                }
-                Instructions::Store { reg_a, reg_b } => {
-                    format!("0001{}{}", Register::binary(reg_a), Register::binary(reg_b))
-                }
-                Instructions::Load { reg_a, reg_b } => {
-                    format!("0000{}{}", Register::binary(reg_a), Register::binary(reg_b))
-                }
-                Instructions::Data { reg, data } => {
-                    let mut binary_data = format!("{:0width$b}", data, width = NUMBER_BITS);
-                    while binary_data.len() > NUMBER_BITS {
-                        binary_data.remove(0);
-                    }
-                    format!("001000{}\n{}", Register::binary(reg), binary_data)
-                }
-                Instructions::JumpRegister { reg } => {
-                    format!("001100{}", Register::binary(reg))
-                }
-                Instructions::JumpAddress { .. } => {
-                    format!("01000000")
-                }
-                Instructions::JumpIf { carry, a_larger, equal, zero, .. } => {
-                    fn bool_char(b: bool) -> char {
-                        match b {
-                            true => '1',
-                            false => '0',
-                        }
-                    }
-                    format!(
-                        "0101{}{}{}{}",
-                        bool_char(carry),
-                        bool_char(a_larger),
-                        bool_char(equal),
-                        bool_char(zero),
-                    )
-                }
-                Instructions::ClearFlags => {
-                    format!("01100000")
-                }
-                Instructions::End => "11001111".to_string(),
-            };
+impl Cli {
+    fn partial_config(&self) -> PartialConfig {
+        PartialConfig {
+            address_bits: self.address_bits,
+            word_bits: self.word_bits,
+            scratch_reg: self.scratch_reg.clone(),
+            format: self.format,
+            strict: self.strict.then_some(true),
+            output: None,
+            comment_chars: self.comment_chars.clone(),
+        }
+    }
+}
 
-        binary_string
+//Warnings and errors are printed the way they always were, without the level and module.
+fn init_logger(trace: bool) {
+    let mut builder = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"));
+    if trace {
+        builder.filter_module(env!("CARGO_CRATE_NAME"), log::LevelFilter::Trace);
     }
+
+    builder
+        .format(|buf, record| match record.level() {
+            Level::Warn => writeln!(buf, "Warning: {}", record.args()),
+            _ => writeln!(buf, "{}", record.args()),
+        })
+        .init();
 }
 
 fn main() {
-    let file_name = "multiplication";
-    let max_num_ram_cells = usize::pow(2, 8);
+    let cli = Cli::parse();
+
+    init_logger(cli.trace);
+
+    //The trace is written out when the guard is dropped at the end of main, or by exit.
+    let _profile_guard = cli.profile.as_deref().map(start_profile);
+
+    let env_config = config::from_env().unwrap_or_else(|e| exit_with_error(e));
+    let file_config = config::from_current_dir().unwrap_or_else(|e| exit_with_error(e));
+    let mut config = Config::from(cli.partial_config().or(env_config).or(file_config));
+    config.append_crc16 = cli.append_crc16;
+    config.features = cli.features.clone();
+    config.line_ending = cli.line_ending;
+    config.base_address = cli.base_address;
+    config.max_breakpoints = cli.max_breakpoints;
+    config.release = cli.release;
+    if let Some(breakpoint_opcode) = cli.breakpoint_opcode {
+        config.breakpoint_opcode = breakpoint_opcode;
+    }
+    if let Some(debug_opcode) = cli.debug_opcode {
+        config.debug_opcode = debug_opcode;
+    }
+    config.max_size = cli.max_size;
+    if cli.no_warn_no_terminal {
+        config.disabled_warnings.push(WarnKind::NoTerminal);
+    }
+    if cli.no_warn_redundant_clf {
+        config.disabled_warnings.push(WarnKind::RedundantClf);
+    }
+    if cli.no_warn_always_false_jif {
+        config.disabled_warnings.push(WarnKind::AlwaysFalseJif);
+    }
+    if cli.no_warn_data_truncated {
+        config.disabled_warnings.push(WarnKind::DataTruncated);
+    }
+    if cli.no_warn_unused_mark {
+        config.disabled_warnings.push(WarnKind::UnusedMark);
+    }
+    if let Some(nop_opcode) = cli.nop_opcode {
+        config.nop_opcode = nop_opcode;
+    }
+    config.isa = cli.isa.as_deref().map(|isa| Isa::load(isa, config.word_bits)).transpose().unwrap_or_else(|e| exit_with_error(e));
+    if let Some(timing_model) = &cli.timing_model {
+        config.timing_model = TimingModel::load(timing_model).unwrap_or_else(|e| exit_with_error(e));
+    }
+    if cli.fill_delay_slots {
+        config.delay_slot_fill = Some(parse_delay_slot_fill(&cli.delay_slot_instruction, &config));
+    }
 
-    let mut file = File::open(format!("programs/{}", file_name)).unwrap();
-    let mut content = String::new();
-    file.read_to_string(&mut content).unwrap();
+    if let Err(errors) = config.validate() {
+        for error in &errors {
+            error!("{}", error);
+        }
+        exit(1);
+    }
 
-    let mut marks_to_machine_code = HashMap::new();
-    let mut real_line_number = 0;
-    let mut machine_code_line_number: i32 = -1;
-    let mut instructions = Vec::new();
-    for line in content.lines() {
-        real_line_number += 1;
+    if let Some(command) = &cli.command {
+        run_command(command, &config).unwrap_or_else(|e| exit_with_error(e));
+        return;
+    }
 
-        let words: Vec<&str> = line.split_whitespace().collect();
+    if cli.repl {
+        repl::run(&config).unwrap_or_else(|e| exit_with_error(e));
+        return;
+    }
 
-        //Empty line.
-        if words.is_empty() {
-            continue;
-        }
+    let input = cli.input.clone().expect("Input is required when not in repl mode.");
+
+    //--output and --format add a target to the ones in the config file, which are only replaced
+    //by the default output when there are none.
+    let mut targets = config.outputs.clone();
+    if cli.output.is_some() || cli.format.is_some() || targets.is_empty() {
+        let path = cli.output.clone().unwrap_or_else(|| {
+            let file_name = input.file_stem().expect("Input path has no file name.");
+            PathBuf::from("machine_code").join(file_name).with_extension(config.format.extension())
+        });
 
-        //Comment.
-        if words[0].starts_with('#') {
-            continue;
+        targets.push(OutputTarget { format: config.format, path, coe_radix: Some(cli.coe_radix) });
+    }
+
+    if let Some(entry) = &cache_entry(&cli, &input, &targets, &config) {
+        if cache::load(&cli.cache_dir, &input).as_ref() == Some(entry) {
+            info!("{} is unchanged, skipping assembly.", input.display());
+            return;
         }
+    }
 
-        //Marked for a jump point.
-        if words[0] == "MARK" {
-            if words.len() != 2 {
-                panic!("{} Invalid formatting for registers.", real_line_number)
-            }
+    info!("Reading {}", input.display());
+    let content = fs::read_to_string(&input).unwrap_or_else(|error|
+        exit_with_error(AssemblerError::Io { path: input.clone(), error })
+    );
+
+    let program = assemble_str(&content, &config).unwrap_or_else(|e| exit_with_error(e));
+
+    for warning in &program.warnings {
+        warn!("{}", warning);
+    }
+
+    //Every target writes its own file, so they can be written at the same time.
+    let write_target = |target: &OutputTarget| {
+        let line_ending = if target.format.is_text() { config.line_ending } else { LineEnding::Lf };
+        write_output(target.writer().as_ref(), &program, &target.path, line_ending)
+    };
+    let result =
+        if cli.no_parallel_output {
+            targets.iter().try_for_each(write_target)
+        } else {
+            targets.par_iter().try_for_each(write_target)
+        };
+
+    result.unwrap_or_else(|e| exit_with_error(e));
+    info!("Assembled {} into {} RAM cells with {} warnings.", input.display(), program.machine_code.len(), program.warnings.len());
+
+    if cli.stats {
+        print_stats(&program, &config);
+    }
+
+    if let Some(stats_path) = &cli.stats_output {
+        write_report(&StatsWriter, &program, stats_path, config.line_ending);
+    }
+
+    if let Some(symbols_path) = &cli.symbols {
+        write_report(&SymbolsWriter, &program, symbols_path, config.line_ending);
+    }
+
+    if let Some(xref_path) = &cli.xref {
+        write_report(&XRefTableWriter, &program, xref_path, config.line_ending);
+    }
 
-            let mark_variable = words[1];
+    if let Some(map_path) = &cli.export_map {
+        write_report(&MapWriter, &program, map_path, config.line_ending);
+    }
+
+    if let Some(listing_path) = &cli.listing {
+        write_report(&ListingWriter { explain: cli.explain, profile: cli.profile_annotate }, &program, listing_path, config.line_ending);
+    }
 
-            marks_to_machine_code.insert(mark_variable.to_string(), machine_code_line_number + 1);
-            continue;
+    if let Some(source_map_path) = &cli.dap_source_map {
+        write_report(&DapSourceMapWriter { source: input.clone() }, &program, source_map_path, config.line_ending);
+    }
+
+    //Hashed again now that the outputs have been written.
+    if let Some(entry) = cache_entry(&cli, &input, &targets, &config) {
+        if let Err(error) = cache::save(&cli.cache_dir, &input, entry) {
+            warn!("unable to write the cache to {}: {}", cli.cache_dir.display(), error);
         }
+    }
+}
 
-        match words[0] {
-            //Values that use at least two registers.
-            "ADD" | "SHR" | "SHL" | "NOT" | "AND" | "OR" | "XOR" | "ST" | "LD" => {
-                if words.len() != 3 {
-                    panic!("{} Invalid formatting for registers.", real_line_number)
-                }
+//Describes the current run for --cache. Returns None when the run can not be cached because it
+//prints to stdout, or when an output is missing and so has to be written again.
+fn cache_entry(cli: &Cli, input: &Path, targets: &[OutputTarget], config: &Config) -> Option<CacheEntry> {
+    if !cli.cache || cli.stats {
+        return None;
+    }
 
-                let reg_a = Register::reg_from_instr(
-                    words[1], real_line_number,
-                );
+    let mut outputs: Vec<&Path> = targets.iter().map(|target| target.path.as_path()).collect();
+    outputs.extend(cli.stats_output.as_deref());
+    outputs.extend(cli.symbols.as_deref());
+    outputs.extend(cli.xref.as_deref());
+    outputs.extend(cli.export_map.as_deref());
+    outputs.extend(cli.listing.as_deref());
+    outputs.extend(cli.dap_source_map.as_deref());
+    if outputs.contains(&Path::new("-")) {
+        return None;
+    }
 
-                let reg_b = Register::reg_from_instr(
-                    words[2], real_line_number,
-                );
+    let mut inputs = vec![input];
+    inputs.extend(cli.isa.as_deref());
+    inputs.extend(cli.timing_model.as_deref());
 
-                machine_code_line_number += 1;
+    Some(CacheEntry {
+        input_hashes: cache::hash_inputs(&inputs).ok()?,
+        config_hash: cache::hash_config(config),
+        output_hash: cache::hash_outputs(&outputs).ok()?,
+    })
+}
 
-                instructions.push(
-                    match words[0] {
-                        "ADD" => {
-                            Instructions::Add { reg_a, reg_b }
-                        }
-                        "SHR" => {
-                            Instructions::Shr { reg_a, reg_b }
-                        }
-                        "SHL" => {
-                            Instructions::Shl { reg_a, reg_b }
-                        }
-                        "NOT" => {
-                            Instructions::Not { reg_a, reg_b }
-                        }
-                        "AND" => {
-                            Instructions::And { reg_a, reg_b }
-                        }
-                        "OR" => {
-                            Instructions::Or { reg_a, reg_b }
-                        }
-                        "XOR" => {
-                            Instructions::XOr { reg_a, reg_b }
-                        }
-                        "ST" => {
-                            Instructions::Store { reg_a, reg_b }
-                        }
-                        "LD" => {
-                            Instructions::Load { reg_a, reg_b }
-                        }
-                        _ => panic!("{} Unknown instruction used, {}", real_line_number, words[0])
-                    }
-                );
-            }
-            "DATA" => {
-                if words.len() != 3 {
-                    panic!("{} Invalid formatting for registers.", real_line_number)
-                }
+fn parse_opcode(value: &str) -> Result<u8, String> {
+    let result =
+        if let Some(binary) = value.strip_prefix("0b") {
+            u8::from_str_radix(binary, 2)
+        } else if let Some(hex) = value.strip_prefix("0x") {
+            u8::from_str_radix(hex, 16)
+        } else {
+            value.parse()
+        };
 
-                let reg = Register::reg_from_instr(
-                    words[1], real_line_number,
-                );
+    result.map_err(|e| e.to_string())
+}
 
-                let data: usize = words[2].parse().expect(
-                    format!("{} Invalid number passed as data {}.", real_line_number, words[2]).as_str()
-                );
+//An address such as 240 or 0xF0.
+fn parse_address(address: &str) -> Result<usize, String> {
+    match address.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => address.parse(),
+    }
+    .map_err(|e| format!("{}: {}", address, e))
+}
 
-                machine_code_line_number += 2;
+//A range of addresses such as 240-255 or 0xF0-0xFF, both ends included.
+fn parse_address_range(value: &str) -> Result<RangeInclusive<usize>, String> {
+    match value.split_once('-') {
+        Some((start, end)) => Ok(parse_address(start)?..=parse_address(end)?),
+        None => Err("expected START-END".to_string()),
+    }
+}
 
-                instructions.push(
-                    Instructions::Data { reg, data }
-                );
-            }
-            "JMPR" => {
-                if words.len() != 2 {
-                    panic!("{} Invalid formatting for registers.", real_line_number)
-                }
+fn parse_delay_slot_fill(source: &str, config: &Config) -> Instructions {
+    let fill = parse_line(source, &mut ParseContext::new(config)).unwrap_or_else(|e| exit_with_error(e));
 
-                let reg = Register::reg_from_instr(
-                    words[1], real_line_number,
-                );
+    match fill.as_slice() {
+        [fill] if !fill.is_branch() => fill.clone(),
+        _ => exit_with_error(format!("Invalid --delay-slot-instruction {}, expected a non branching instruction.", source)),
+    }
+}
 
-                machine_code_line_number += 1;
+fn run_command(command: &Command, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = std::io::stdout();
 
-                instructions.push(
-                    Instructions::JumpRegister { reg }
-                );
+    match command {
+        Command::Info { format } => {
+            let info = info::isa_info(config);
+            match format {
+                ReportFormat::Table => info::write_table(&info, &mut stdout)?,
+                ReportFormat::Json => writeln!(stdout, "{}", serde_json::to_string_pretty(&info)?)?,
             }
-            "JMP" => {
-                if words.len() != 2 {
-                    panic!("{} Invalid formatting for registers.", real_line_number)
-                }
-
-                let mark_variable = words[1];
+        }
+        Command::Registers { program, format } => {
+            let aliases =
+                match program {
+                    Some(program) => assemble_str(&read_file(program)?, config)?.metadata.register_aliases,
+                    None => IndexMap::new(),
+                };
+
+            let registers = info::register_info(&aliases);
+            match format {
+                ReportFormat::Table => info::write_registers(&registers, &mut stdout)?,
+                ReportFormat::Json => writeln!(stdout, "{}", serde_json::to_string_pretty(&registers)?)?,
+            }
+        }
+        Command::Opcodes { format } => {
+            let table = info::opcode_table();
+            match format {
+                ReportFormat::Table => info::write_opcode_table(&table, &mut stdout)?,
+                ReportFormat::Json => writeln!(stdout, "{}", serde_json::to_string_pretty(&table)?)?,
+            }
+        }
+        Command::Explain { code } => {
+            match explanation(&code.to_uppercase()) {
+                Some(explanation) => writeln!(stdout, "{}", explanation)?,
+                None => return Err(format!("Unknown error code {}, codes run from E001 to E{:03}.", code, EXPLANATIONS.len()).into()),
+            }
+        }
+        Command::Grammar => write!(stdout, "{}", Assembler::grammar_ebnf())?,
+        Command::Disassemble { input, hex, hex_separator, symbols } => {
+            let machine_code =
+                match (input, hex) {
+                    (_, Some(hex)) => parse_hex(hex, hex_separator)?,
+                    (Some(input), None) => read_machine_code(input)?,
+                    (None, None) => unreachable!("clap requires the input or --hex"),
+                };
+
+            let symbols = match symbols {
+                Some(path) => Some(parse_symbols(&read_file(path)?)?),
+                None => None,
+            };
 
-                machine_code_line_number += 2;
+            let instructions = disassemble(&machine_code, symbols.as_ref())?;
+            write_source(&instructions, symbols.as_ref(), &mut stdout)?;
+        }
+        Command::Patch { input, patch, output } => {
+            let machine_code = read_machine_code(input)?;
+            let patched = apply_patch(&machine_code, &read_file(patch)?, config)?;
 
-                instructions.push(
-                    Instructions::JumpAddress { mark: mark_variable.to_string() }
-                );
+            for warning in &patched.warnings {
+                warn!("{}", warning);
             }
-            "JIF" => {
-                if words.len() != 3 {
-                    panic!("{} Invalid formatting for registers.", real_line_number)
+
+            atomic_write(output, |file| {
+                for cell in &patched.machine_code {
+                    writeln!(file, "{}", cell)?;
                 }
+                Ok(())
+            }).map_err(|error| AssemblerError::Io { path: output.clone(), error })?;
+        }
+        Command::CreatePatch { original, modified } => {
+            let patch = create_patch(&read_machine_code(original)?, &read_machine_code(modified)?)?;
+            write!(stdout, "{}", patch)?;
+        }
+        Command::Analyze { input, histogram, complexity, format } => {
+            let disassembled = disassemble(&read_machine_code(input)?, None)?;
+            let instructions: Vec<Instructions> = disassembled.iter()
+                .map(|(_, instruction)| instruction.clone())
+                .collect();
+
+            let histogram = histogram.then(|| instruction_histogram(&instructions));
+            let complexity = complexity.then(|| ComplexityReport::new(&disassembled, &IndexMap::new()));
+
+            match (format, histogram, complexity) {
+                (ReportFormat::Table, histogram, complexity) => {
+                    if let Some(histogram) = &histogram {
+                        write_histogram(histogram, &mut stdout)?;
+                    }
+                    if let Some(complexity) = complexity {
+                        writeln!(stdout, "{}", complexity)?;
+                    }
+                }
+                (ReportFormat::Json, Some(histogram), None) => writeln!(stdout, "{}", serde_json::to_string_pretty(&histogram)?)?,
+                (ReportFormat::Json, None, Some(complexity)) => writeln!(stdout, "{}", serde_json::to_string_pretty(&complexity)?)?,
+                (ReportFormat::Json, histogram, complexity) => {
+                    let report = serde_json::json!({ "histogram": histogram, "complexity": complexity });
+                    writeln!(stdout, "{}", serde_json::to_string_pretty(&report)?)?;
+                }
+            }
+        }
+        Command::Viz { format: VizFormat::Svg, program, output, size, mmio } => {
+            let assembled = assemble_str(&read_file(program)?, config)?;
+            let writer = SvgWriter {
+                size: size.unwrap_or_else(|| usize::pow(2, config.address_bits as u32)),
+                mmio: mmio.clone(),
+            };
 
-                let mut carry = false;
-                let mut a_larger = false;
-                let mut equal = false;
-                let mut zero = false;
+            let output = output.clone().unwrap_or_else(|| {
+                let mut path = program.clone().into_os_string();
+                path.push(".svg");
+                PathBuf::from(path)
+            });
 
-                for c in words[1].chars() {
-                    match c {
-                        'C' => {
-                            carry = true;
-                        }
-                        'A' => {
-                            a_larger = true;
-                        }
-                        'E' => {
-                            equal = true;
-                        }
-                        'Z' => {
-                            zero = true;
+            write_output(&writer, &assembled, &output, config.line_ending)?;
+        }
+        Command::Coverage { trace, program, output } => {
+            let assembled = assemble_str(&read_file(program)?, config)?;
+            let coverage = Coverage::new(&assembled, &parse_trace(&read_file(trace)?, config)?);
+
+            let output = output.clone().unwrap_or_else(|| {
+                let mut path = program.clone().into_os_string();
+                path.push(".cov");
+                PathBuf::from(path)
+            });
+
+            atomic_write(&output, |file| coverage.write_listing(&assembled, file))
+                .map_err(|error| AssemblerError::Io { path: output.clone(), error })?;
+
+            writeln!(stdout, "{}", coverage)?;
+        }
+        Command::Lsp => lsp::run(config)?,
+        Command::Fmt { input, in_place } => {
+            let formatted = Formatter::format(&read_file(input)?, &FormatConfig { comment_chars: config.comment_chars.clone(), ..FormatConfig::default() });
+
+            if *in_place {
+                atomic_write(input, |file| file.write_all(formatted.as_bytes())).map_err(|error| AssemblerError::Io { path: input.clone(), error })?;
+            } else {
+                write!(stdout, "{}", formatted)?;
+            }
+        }
+        Command::Batch { manifest, parallel, stats } => {
+            let entries = parse_manifest(&read_file(manifest)?)?;
+            let results = run_batch(&entries, config, *parallel);
+
+            for (entry, result) in entries.iter().zip(&results) {
+                match &result.result {
+                    Ok(warnings) => {
+                        for warning in warnings {
+                            warn!("{}: {}", entry.input.display(), warning);
                         }
-                        _ => panic!("{} Invalid formatting for JIF command {}.", real_line_number, c)
                     }
+                    //Io errors already name the file.
+                    Err(e @ AssemblerError::Io { .. }) => error!("{}", e),
+                    Err(e) => error!("{}: {}", entry.input.display(), e),
+                }
+            }
+
+            if *stats {
+                for (entry, result) in entries.iter().zip(&results) {
+                    writeln!(stdout, "{:>10.3} ms  {}", result.elapsed.as_secs_f64() * 1000.0, entry.input.display())?;
                 }
+            }
 
-                let mark_variable = words[2];
+            let summary = BatchSummary::new(&results);
+            writeln!(stdout, "{}", summary)?;
 
-                machine_code_line_number += 2;
+            if summary.failed > 0 {
+                exit(1);
+            }
+        }
+        Command::VerifySource { program, source } => {
+            let bytes = fs::read(program).map_err(|error| AssemblerError::Io { path: program.clone(), error })?;
+            let header =
+                match BinaryHeader::parse(&bytes) {
+                    Some((header, _)) => header,
+                    None => return Err(format!("{} is not in the binary-v2 format.", program.display()).into()),
+                };
+
+            if header.source_hash != source_hash(&read_file(source)?) {
+                error!("{} was not assembled from {}.", program.display(), source.display());
+                exit(1);
+            }
 
-                instructions.push(
-                    Instructions::JumpIf { carry, a_larger, equal, zero, mark: mark_variable.to_string() }
-                );
+            writeln!(stdout, "{} was assembled from {}.", program.display(), source.display())?;
+        }
+        Command::VerifyCrc16 { program } => {
+            if !verify_crc16(&read_machine_code(program)?) {
+                error!("The CRC-16 at the end of {} does not match the program.", program.display());
+                exit(1);
             }
-            "CLF" => {
-                machine_code_line_number += 1;
 
-                instructions.push(
-                    Instructions::ClearFlags
-                );
+            writeln!(stdout, "The CRC-16 of {} matches.", program.display())?;
+        }
+        Command::SelfTest => {
+            let mut failed = 0;
+            for test in &SELF_TESTS {
+                let result = run_self_test(test);
+                if result != SelfTestResult::Passed {
+                    failed += 1;
+                }
+
+                writeln!(stdout, "{}: {}", test.name, result)?;
             }
-            "END" => {
-                machine_code_line_number += 1;
 
-                instructions.push(
-                    Instructions::End
-                );
+            if failed > 0 {
+                error!("{} of {} self tests failed.", failed, SELF_TESTS.len());
+                exit(1);
             }
-            _ => panic!("{} Unknown instruction used, {}", real_line_number, words[0])
-        };
+
+            writeln!(stdout, "All {} self tests passed.", SELF_TESTS.len())?;
+        }
     }
 
-    let mut final_build: Vec<String> = Vec::new();
-    for instruction in instructions {
-        let mark =
-            match &instruction {
-                Instructions::JumpAddress { mark } => {
-                    let machine_line = marks_to_machine_code.get(mark).expect(
-                        format!("Mark {} not found.", mark).as_str()
-                    );
+    Ok(())
+}
 
-                    let binary_input_number = format!("{:0width$b}", machine_line, width = NUMBER_BITS);
+fn read_file(path: &Path) -> Result<String, AssemblerError> {
+    fs::read_to_string(path).map_err(|error| AssemblerError::Io { path: path.to_path_buf(), error })
+}
 
-                    Some(binary_input_number)
-                }
-                Instructions::JumpIf { mark, .. } => {
-                    let machine_line = marks_to_machine_code.get(mark).expect(
-                        format!("Mark {} not found.", mark).as_str()
-                    );
+//Reads a file in the ms format, one binary string per line, or in the binary-v2 format.
+fn read_machine_code(path: &Path) -> Result<Vec<String>, AssemblerError> {
+    let bytes = fs::read(path).map_err(|error| AssemblerError::Io { path: path.to_path_buf(), error })?;
 
-                    let binary_input_number = format!("{:0width$b}", machine_line, width = NUMBER_BITS);
+    if let Some((header, program)) = BinaryHeader::parse(&bytes) {
+        return Ok(from_bytes(program, header.word_bits as usize));
+    }
 
-                    Some(binary_input_number)
-                }
-                _ => None
-            };
+    let content = String::from_utf8_lossy(&bytes);
 
-        final_build.push(
-            Instructions::binary(
-                instruction
-            )
-        );
+    Ok(content.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect())
+}
 
-        if let Some(mark) = mark {
-            final_build.push(mark);
-        }
+fn print_stats(program: &AssembledProgram, config: &Config) {
+    let used = program.machine_code.len();
+    let max_num_ram_cells = usize::pow(2, config.address_bits as u32);
+
+    println!("RAM: {} of {} cells ({:.1}%)", used, max_num_ram_cells, percentage(used, max_num_ram_cells));
+    if let Some(budget) = program.budget {
+        println!("Budget: {} of {} cells ({:.1}%)", used, budget, percentage(used, budget));
+    }
+    if let Some(time) = program.estimated_time_us() {
+        println!("Estimated time: {:.3} us ({} cycles, each instruction run once)", time, program.estimated_cycles());
     }
+}
 
-    final_build.push(
-        Instructions::binary(
-            Instructions::End
-        )
-    );
+//Writes the program to a file, a path of - writes to stdout.
+fn write_output(writer: &dyn OutputWriter, program: &AssembledProgram, path: &Path, line_ending: LineEnding) -> Result<(), AssemblerError> {
+    let write = |out: &mut dyn Write| writer.write(program, &mut LineEndingWriter::new(out, line_ending));
 
-    if machine_code_line_number > max_num_ram_cells as i32 {
-        panic!("File contains too many instructions. {} found, {} maximum.", machine_code_line_number, max_num_ram_cells);
-    }
+    let result =
+        if path == Path::new("-") {
+            write(&mut std::io::stdout())
+        } else {
+            info!("Writing {}", path.display());
+            atomic_write(path, write)
+        };
+
+    result.map_err(|error| AssemblerError::Io { path: path.to_path_buf(), error })
+}
 
-    let mut output_file = File::create(format!("machine_code/{}.ms", file_name).as_str()).expect("Failed to create output file.");
+//Writes an extra report about the program, a path of - writes to stdout.
+fn write_report(writer: &dyn OutputWriter, program: &AssembledProgram, path: &Path, line_ending: LineEnding) {
+    write_output(writer, program, path, line_ending).unwrap_or_else(|e| exit_with_error(e));
+}
 
-    for s in final_build {
-        writeln!(output_file, "{}", s).expect("Unable to write to file.");
+//The trace of --profile. It is kept here rather than in main so exit can write it out too, as
+//process::exit does not run destructors.
+static PROFILE: Mutex<Option<FlushGuard>> = Mutex::new(None);
+
+//Writes out the trace of --profile when dropped.
+struct ProfileGuard;
+
+impl Drop for ProfileGuard {
+    fn drop(&mut self) {
+        if let Ok(mut profile) = PROFILE.lock() {
+            profile.take();
+        }
     }
 }
+
+fn start_profile(path: &Path) -> ProfileGuard {
+    let (chrome_layer, guard) = ChromeLayerBuilder::new().file(path).build();
+    //Not init, which would replace env_logger as the logger.
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(chrome_layer))
+        .expect("No other tracing subscriber is set.");
+    *PROFILE.lock().expect("Nothing panics while holding the profile.") = Some(guard);
+    ProfileGuard
+}
+
+fn exit_with_error(error: impl Display) -> ! {
+    error!("{}", error);
+    exit(1)
+}
+
+//Exits once the trace of --profile is written out.
+fn exit(code: i32) -> ! {
+    drop(ProfileGuard);
+    process::exit(code)
+}
@@ -0,0 +1,100 @@
+//Marks can be grouped into modules by writing them as MODULE.NAME. After
+//
+//  .module NAME
+//
+//a MARK without a module belongs to NAME, and a jump to a name without a module goes to the mark
+//of that name in NAME when it has one, otherwise to the mark outside of any module. The module
+//lasts until the next .module. Directives such as .entry and .assert always use the full name.
+//
+//A jump can add a number of RAM cells to the address of its mark, as in JMP LOOP+2 or
+//JIF E START-1.
+//
+//Marks made by the assembler start with __internal_, which a MARK of the program can not use.
+
+use std::fmt;
+
+use indexmap::IndexMap;
+
+use crate::error::AssemblerError;
+
+pub const INTERNAL_MARK_PREFIX: &str = "__internal_";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarkName {
+    pub module: Option<String>,
+    pub name: String,
+}
+
+impl MarkName {
+    //A name has at most one dot, with words on both sides of it.
+    pub fn parse(s: &str) -> Result<MarkName, AssemblerError> {
+        let invalid = || AssemblerError::InvalidMarkName { name: s.to_string() };
+
+        let mark =
+            match s.split_once('.') {
+                Some((module, name)) => MarkName { module: Some(module.to_string()), name: name.to_string() },
+                None => MarkName { module: None, name: s.to_string() },
+            };
+
+        if mark.name.is_empty() || mark.name.contains('.') || mark.module.as_deref().is_some_and(str::is_empty) {
+            return Err(invalid());
+        }
+
+        Ok(mark)
+    }
+
+    pub fn is_internal(&self) -> bool {
+        self.name.starts_with(INTERNAL_MARK_PREFIX)
+    }
+
+    //The mark defined by a MARK line while module is the current module.
+    pub fn in_module(self, module: Option<&str>) -> MarkName {
+        match (self.module, module) {
+            (None, Some(module)) => MarkName { module: Some(module.to_string()), name: self.name },
+            (current, _) => MarkName { module: current, name: self.name },
+        }
+    }
+}
+
+impl fmt::Display for MarkName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.module {
+            Some(module) => write!(f, "{}.{}", module, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+//Splits NAME+N or NAME-N into the name and the offset, a name without a number after its last
+//+ or - has an offset of 0.
+pub fn split_offset(mark: &str) -> (&str, i64) {
+    let Some(sign) = mark.rfind(['+', '-']).filter(|sign| *sign > 0) else {
+        return (mark, 0);
+    };
+
+    let digits = &mark[sign + 1..];
+    match digits.parse::<i64>() {
+        Ok(offset) if digits.chars().all(|c| c.is_ascii_digit()) => {
+            (&mark[..sign], if mark[sign..].starts_with('-') { -offset } else { offset })
+        }
+        _ => (mark, 0),
+    }
+}
+
+//Address of the mark with its offset added, None when the mark is not defined.
+pub fn mark_address(mark: &str, marks: &IndexMap<String, i32>) -> Option<i64> {
+    let (name, offset) = split_offset(mark);
+    marks.get(name).map(|address| *address as i64 + offset)
+}
+
+//Points a jump written inside module at the mark of the module, once every mark is known.
+pub fn resolve_in_module(mark: &mut String, module: &str, marks: &IndexMap<String, i32>) {
+    if mark.contains('.') {
+        return;
+    }
+
+    let qualified = format!("{}.{}", module, mark);
+    if marks.contains_key(split_offset(&qualified).0) {
+        *mark = qualified;
+    }
+}
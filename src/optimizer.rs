@@ -0,0 +1,125 @@
+use std::fmt;
+
+use log::debug;
+
+use crate::analysis::sets_flags;
+use crate::ast::{Program, Statement};
+use crate::instructions::Instructions;
+
+pub const DEFAULT_MAX_ITERATIONS: usize = 20;
+
+//A pass that rewrites the program one statement at a time, such as a peephole optimization.
+//Each statement is replaced by the statements returned, none removes it.
+pub trait AstTransform {
+    fn transform_statement(&mut self, stmt: Statement) -> Vec<Statement>;
+
+    //Called before the first statement of each program, so state kept between statements starts
+    //over.
+    fn reset(&mut self) {}
+}
+
+//Removes a CLF when the flags are still clear from the previous one. A jump can land on a
+//MARK between the two, so nothing is known about the flags after one.
+#[derive(Default)]
+pub struct RedundantClearFlags {
+    flags_clear: bool,
+}
+
+impl AstTransform for RedundantClearFlags {
+    fn transform_statement(&mut self, stmt: Statement) -> Vec<Statement> {
+        match &stmt {
+            Statement::Instruction(spanned) => {
+                match spanned.instruction {
+                    Instructions::ClearFlags if self.flags_clear => return Vec::new(),
+                    Instructions::ClearFlags => self.flags_clear = true,
+                    _ if sets_flags(&spanned.instruction) => self.flags_clear = false,
+                    _ => {}
+                }
+            }
+            Statement::Mark(_) => self.flags_clear = false,
+            Statement::Directive(_) => {}
+        }
+
+        vec![stmt]
+    }
+
+    fn reset(&mut self) {
+        self.flags_clear = false;
+    }
+}
+
+#[derive(Debug)]
+pub enum OptimizerError {
+    DidNotConverge { iterations: usize },
+}
+
+impl fmt::Display for OptimizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptimizerError::DidNotConverge { iterations } => {
+                write!(f, "Optimization passes were still changing the program after {} iterations.", iterations)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OptimizerError {}
+
+pub struct Optimizer {
+    //Most times every pass is run before giving up.
+    pub max_iterations: usize,
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Optimizer { max_iterations: DEFAULT_MAX_ITERATIONS }
+    }
+}
+
+//Number of positions holding a different statement, statements added or removed at the end
+//count as changed.
+fn count_changes(before: &[Statement], after: &[Statement]) -> usize {
+    let changed = before.iter().zip(after).filter(|(a, b)| a != b).count();
+    changed + before.len().abs_diff(after.len())
+}
+
+impl Optimizer {
+    //Runs each transform over every statement of the program, the output of one is the input of
+    //the next.
+    pub fn apply_transforms(program: Program, transforms: &mut [Box<dyn AstTransform>]) -> Program {
+        let mut program = program;
+
+        for transform in transforms {
+            transform.reset();
+            program.statements = program.statements.into_iter()
+                .flat_map(|stmt| transform.transform_statement(stmt))
+                .collect();
+        }
+
+        program
+    }
+
+    //Applies the transforms until a full round leaves the program unchanged. Returns the program
+    //and the number of rounds run, including the final unchanged one.
+    pub fn optimize_to_fixed_point(
+        &self,
+        program: Program,
+        transforms: &mut [Box<dyn AstTransform>],
+    ) -> Result<(Program, usize), OptimizerError> {
+        let mut program = program;
+
+        for iteration in 1..=self.max_iterations {
+            let before = program.statements.clone();
+            program = Optimizer::apply_transforms(program, transforms);
+
+            let changes = count_changes(&before, &program.statements);
+            debug!("Optimization iteration {} changed {} statements.", iteration, changes);
+
+            if changes == 0 {
+                return Ok((program, iteration));
+            }
+        }
+
+        Err(OptimizerError::DidNotConverge { iterations: self.max_iterations })
+    }
+}
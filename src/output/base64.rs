@@ -0,0 +1,27 @@
+use std::io::{self, Write};
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE};
+use base64::Engine;
+
+use crate::assembler::AssembledProgram;
+use crate::output::{to_bytes, OutputWriter};
+
+//Writes the bytes of the bin format as base64 with = padding on a single line, with the URL
+//safe alphabet when url_safe is set.
+pub struct Base64Writer {
+    pub url_safe: bool,
+}
+
+impl OutputWriter for Base64Writer {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        let bytes = to_bytes(&program.machine_code);
+
+        let encoded =
+            match self.url_safe {
+                true => URL_SAFE.encode(bytes),
+                false => STANDARD.encode(bytes),
+            };
+
+        writeln!(out, "{}", encoded)
+    }
+}
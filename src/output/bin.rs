@@ -0,0 +1,99 @@
+use std::io::{self, Write};
+
+use crate::assembler::AssembledProgram;
+use crate::output::{to_bytes, OutputWriter};
+
+pub const MAGIC: [u8; 4] = [0x4C, 0x43, 0x50, 0x55];
+pub const HEADER_LEN: usize = 44;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryFormat {
+    //Only the bytes of the program.
+    V1,
+    //A header describing the program followed by its bytes.
+    V2,
+}
+
+//The header of the v2 format, 44 bytes in total: the magic bytes "LCPU", the version, the
+//address and word bits, the number of instructions and the entry point as little endian u16s, a
+//reserved byte and the SHA-256 hash of the assembled source. Files from before the entry point
+//was added have 0 there, which is where they start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BinaryHeader {
+    pub version: u8,
+    pub address_bits: u8,
+    pub word_bits: u8,
+    pub instruction_count: u16,
+    pub entry_point: u16,
+    pub source_hash: [u8; 32],
+}
+
+impl BinaryHeader {
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let count = self.instruction_count.to_le_bytes();
+
+        let mut bytes = [0; HEADER_LEN];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4] = self.version;
+        bytes[5] = self.address_bits;
+        bytes[6] = self.word_bits;
+        bytes[7..9].copy_from_slice(&count);
+        bytes[9..11].copy_from_slice(&self.entry_point.to_le_bytes());
+        bytes[12..].copy_from_slice(&self.source_hash);
+        bytes
+    }
+
+    //Returns the header and the bytes after it, or None when the bytes do not start with one.
+    pub fn parse(bytes: &[u8]) -> Option<(BinaryHeader, &[u8])> {
+        if bytes.len() < HEADER_LEN || bytes[..4] != MAGIC {
+            return None;
+        }
+
+        let header = BinaryHeader {
+            version: bytes[4],
+            address_bits: bytes[5],
+            word_bits: bytes[6],
+            instruction_count: u16::from_le_bytes([bytes[7], bytes[8]]),
+            entry_point: u16::from_le_bytes([bytes[9], bytes[10]]),
+            source_hash: bytes[12..HEADER_LEN].try_into().unwrap(),
+        };
+
+        Some((header, &bytes[HEADER_LEN..]))
+    }
+}
+
+//Converts big endian bytes back into RAM cells, the reverse of to_bytes.
+pub fn from_bytes(bytes: &[u8], word_bits: usize) -> Vec<String> {
+    let bytes_per_cell = word_bits.div_ceil(8).max(1);
+
+    bytes.chunks(bytes_per_cell)
+        .map(|chunk| {
+            let value = chunk.iter().fold(0u64, |value, byte| (value << 8) | *byte as u64);
+            format!("{:0width$b}", value, width = word_bits)
+        })
+        .collect()
+}
+
+//Writes the bytes of the program, after a header for the v2 format.
+pub struct BinWriter {
+    pub format: BinaryFormat,
+}
+
+impl OutputWriter for BinWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        if self.format == BinaryFormat::V2 {
+            let header = BinaryHeader {
+                version: 2,
+                address_bits: program.address_bits as u8,
+                word_bits: program.word_bits as u8,
+                instruction_count: program.instruction_count() as u16,
+                entry_point: program.entry_point as u16,
+                source_hash: program.source_hash,
+            };
+
+            out.write_all(&header.to_bytes())?;
+        }
+
+        out.write_all(&to_bytes(&program.machine_code))
+    }
+}
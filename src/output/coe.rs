@@ -0,0 +1,56 @@
+use std::io::{self, Write};
+
+use serde::Deserialize;
+
+use crate::assembler::AssembledProgram;
+use crate::output::OutputWriter;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CoeRadix {
+    #[cfg_attr(feature = "cli", value(name = "2"))]
+    #[serde(rename = "2")]
+    Binary,
+    #[cfg_attr(feature = "cli", value(name = "16"))]
+    #[serde(rename = "16")]
+    Hex,
+}
+
+impl CoeRadix {
+    fn value(&self) -> u32 {
+        match self {
+            CoeRadix::Binary => 2,
+            CoeRadix::Hex => 16,
+        }
+    }
+}
+
+//Writes a Xilinx Vivado .coe file used to initialize block RAM.
+pub struct CoeWriter {
+    pub radix: CoeRadix,
+}
+
+impl CoeWriter {
+    fn encode(&self, binary: &str) -> String {
+        match self.radix {
+            CoeRadix::Binary => binary.to_string(),
+            CoeRadix::Hex => {
+                let value = u32::from_str_radix(binary, 2).expect("Machine code must be a binary string.");
+                let width = binary.len().div_ceil(4);
+                format!("{:0width$X}", value, width = width)
+            }
+        }
+    }
+}
+
+impl OutputWriter for CoeWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "memory_initialization_radix={};", self.radix.value())?;
+        writeln!(out, "memory_initialization_vector=")?;
+
+        let values: Vec<String> = program.machine_code.iter().map(|s| self.encode(s)).collect();
+        writeln!(out, "{};", values.join(",\n"))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::assembler::AssembledProgram;
+use crate::output::OutputWriter;
+
+#[derive(Serialize)]
+struct Source {
+    path: PathBuf,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Mapping {
+    generated_line: usize,
+    original_line: usize,
+}
+
+#[derive(Serialize)]
+struct SourceMap {
+    sources: Vec<Source>,
+    //Clock speed from .frequency, for timing analysis.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency: Option<u64>,
+    //One list of mappings per source.
+    mappings: Vec<Vec<Mapping>>,
+    //Address of each .breakpoint.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    breakpoints: Vec<usize>,
+}
+
+//Writes a Debug Adapter Protocol source map, the generated line of each mapping is a RAM address.
+pub struct DapSourceMapWriter {
+    pub source: PathBuf,
+}
+
+impl OutputWriter for DapSourceMapWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        let mappings = program.instructions.iter()
+            .flat_map(|assembled| {
+                (assembled.address..assembled.address + assembled.size)
+                    .map(|address| Mapping { generated_line: address, original_line: assembled.line })
+            })
+            .collect();
+
+        let source_map = SourceMap {
+            sources: vec![Source { path: self.source.clone() }],
+            frequency: program.metadata.frequency,
+            mappings: vec![mappings],
+            breakpoints: program.breakpoint_addresses(),
+        };
+
+        writeln!(out, "{}", serde_json::to_string_pretty(&source_map)?)
+    }
+}
@@ -0,0 +1,34 @@
+use std::io::{self, Write};
+
+use crate::assembler::AssembledProgram;
+use crate::output::{to_bytes, OutputWriter};
+
+const BYTES_PER_RECORD: usize = 16;
+
+//Writes an Intel HEX file made of data records followed by an end of file record.
+pub struct IhexWriter;
+
+fn write_record(out: &mut dyn Write, address: u16, record_type: u8, data: &[u8]) -> io::Result<()> {
+    let mut record = vec![data.len() as u8, (address >> 8) as u8, address as u8, record_type];
+    record.extend_from_slice(data);
+
+    let checksum = record.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)).wrapping_neg();
+
+    write!(out, ":")?;
+    for b in record {
+        write!(out, "{:02X}", b)?;
+    }
+    writeln!(out, "{:02X}", checksum)
+}
+
+impl OutputWriter for IhexWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        let bytes = to_bytes(&program.machine_code);
+
+        for (i, chunk) in bytes.chunks(BYTES_PER_RECORD).enumerate() {
+            write_record(out, (i * BYTES_PER_RECORD) as u16, 0x00, chunk)?;
+        }
+
+        write_record(out, 0, 0x01, &[])
+    }
+}
@@ -0,0 +1,58 @@
+use std::io::{self, Write};
+
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum), value(rename_all = "lowercase"))]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    //Needed by tools such as Xilinx ISE.
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+            LineEnding::Cr => b"\r",
+        }
+    }
+}
+
+//Replaces every \n written through it with the line ending, so the writers of each format can
+//keep using writeln!.
+pub struct LineEndingWriter<W: Write> {
+    inner: W,
+    line_ending: LineEnding,
+}
+
+impl<W: Write> LineEndingWriter<W> {
+    pub fn new(inner: W, line_ending: LineEnding) -> Self {
+        LineEndingWriter { inner, line_ending }
+    }
+}
+
+impl<W: Write> Write for LineEndingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.line_ending == LineEnding::Lf {
+            return self.inner.write(buf);
+        }
+
+        for (i, line) in buf.split(|byte| *byte == b'\n').enumerate() {
+            if i > 0 {
+                self.inner.write_all(self.line_ending.as_bytes())?;
+            }
+            self.inner.write_all(line)?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,33 @@
+use std::io::{self, Write};
+
+use crate::assembler::AssembledProgram;
+use crate::output::OutputWriter;
+
+//Writes the address, RAM cells and instruction of every assembled instruction, one per line.
+//With explain set each line ends with a comment describing the instruction. With profile set the
+//cycles the instruction takes, from the timing model, come before it.
+pub struct ListingWriter {
+    pub explain: bool,
+    pub profile: bool,
+}
+
+impl OutputWriter for ListingWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        for assembled in &program.instructions {
+            let cells = program.machine_code[assembled.address..assembled.address + assembled.size].join(" ");
+            let instruction =
+                match self.profile {
+                    true => format!("{:>3}  {}", program.timing_model.cycles(assembled.instruction.mnemonic()), assembled.instruction),
+                    false => assembled.instruction.to_string(),
+                };
+
+            if self.explain {
+                writeln!(out, "{:>4}  {:<24}  {:<20}  ; {}", assembled.address, cells, instruction, assembled.instruction.explanation())?;
+            } else {
+                writeln!(out, "{:>4}  {:<24}  {}", assembled.address, cells, instruction)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::{self, Write};
+
+use crate::assembler::AssembledProgram;
+use crate::output::OutputWriter;
+
+const ENTRIES_PER_LINE: usize = 8;
+
+//Writes a Logisim 2.x RAM image, "v2.0 raw" followed by one hex value per RAM cell. A run of
+//equal values is written once with its length, such as 5*00.
+pub struct LogisimWriter;
+
+impl OutputWriter for LogisimWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        let digits = program.word_bits.div_ceil(4).max(1);
+
+        let mut runs: Vec<(usize, u64)> = Vec::new();
+        for cell in &program.machine_code {
+            let value = u64::from_str_radix(cell, 2).expect("Machine code must be a binary string.");
+            match runs.last_mut() {
+                Some((count, last)) if *last == value => *count += 1,
+                _ => runs.push((1, value)),
+            }
+        }
+
+        writeln!(out, "v2.0 raw")?;
+
+        for line in runs.chunks(ENTRIES_PER_LINE) {
+            let entries: Vec<String> = line.iter()
+                .map(|(count, value)| match count {
+                    1 => format!("{:0digits$x}", value),
+                    count => format!("{}*{:0digits$x}", count, value),
+                })
+                .collect();
+
+            writeln!(out, "{}", entries.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
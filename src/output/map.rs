@@ -0,0 +1,60 @@
+use std::io::{self, Write};
+
+use crate::assembler::AssembledProgram;
+use crate::output::OutputWriter;
+
+//Writes a map of the program for ROM tools, one record per line starting with its kind so a
+//script can pick out the lines it needs.
+//
+//  ENTRY 0x00
+//  SIZE 42
+//  SYMBOL 0x02 math.multiply
+//  SECTION math 0x02 12
+//
+//SIZE is the number of RAM cells, SYMBOL lines are sorted by address and each SECTION is a
+//.module with its first address and the RAM cells in it. Marks made by the assembler are left out.
+pub struct MapWriter;
+
+impl OutputWriter for MapWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        let width = program.address_bits.div_ceil(4);
+        let hex = |address: usize| format!("0x{:0width$X}", address, width = width);
+
+        writeln!(out, "ENTRY {}", hex(program.entry_point))?;
+        writeln!(out, "SIZE {}", program.machine_code.len())?;
+
+        let mut marks: Vec<(&String, &usize)> = program.marks.iter()
+            .filter(|(mark, _)| !mark.starts_with("__"))
+            .collect();
+        marks.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+
+        for (mark, address) in marks {
+            writeln!(out, "SYMBOL {} {}", hex(*address), mark)?;
+        }
+
+        for (name, start, size) in sections(program) {
+            writeln!(out, "SECTION {} {} {}", name, hex(start), size)?;
+        }
+
+        Ok(())
+    }
+}
+
+//The instructions from the line of each .module up to the next one, as (name, first address, RAM
+//cells). A module used more than once is a section for each time, one without instructions is
+//left out.
+fn sections(program: &AssembledProgram) -> Vec<(&str, usize, usize)> {
+    let modules = &program.metadata.modules;
+
+    modules.iter().enumerate()
+        .filter_map(|(i, (name, line))| {
+            let end_line = modules.get(i + 1).map_or(usize::MAX, |(_, next)| *next);
+            let instructions: Vec<_> = program.instructions.iter()
+                .filter(|assembled| (*line..end_line).contains(&assembled.line))
+                .collect();
+
+            let start = instructions.first()?.address;
+            Some((name.as_str(), start, instructions.iter().map(|assembled| assembled.size).sum()))
+        })
+        .collect()
+}
@@ -0,0 +1,147 @@
+pub mod base64;
+pub mod bin;
+pub mod coe;
+pub mod dap;
+pub mod ihex;
+pub mod line_ending;
+pub mod listing;
+pub mod logisim;
+pub mod map;
+pub mod ms;
+pub mod rust_array;
+pub mod stats;
+pub mod svg;
+pub mod symbols;
+pub mod xref;
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use strum::{EnumIter, EnumString, IntoStaticStr};
+
+use crate::assembler::AssembledProgram;
+use crate::output::base64::Base64Writer;
+use crate::output::bin::{BinWriter, BinaryFormat};
+use crate::output::coe::{CoeRadix, CoeWriter};
+use crate::output::ihex::IhexWriter;
+use crate::output::logisim::LogisimWriter;
+use crate::output::ms::MsWriter;
+use crate::output::rust_array::RustArrayWriter;
+use crate::output::symbols::SymbolsWriter;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, EnumIter, EnumString, IntoStaticStr)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+pub enum OutputFormat {
+    //One binary string per RAM cell.
+    Ms,
+    //Xilinx Vivado memory initialization file.
+    Coe,
+    //Intel HEX.
+    Ihex,
+    //Raw bytes.
+    Bin,
+    //Raw bytes after a header describing the program.
+    BinaryV2,
+    //Rust source declaring the program as a byte array.
+    RustArray,
+    //Mark names and their addresses.
+    Symbols,
+    //Logisim 2.x RAM image.
+    Logisim,
+    //Raw bytes as base64.
+    Base64,
+    //Raw bytes as base64 with the URL safe alphabet.
+    Base64Url,
+}
+
+impl OutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Ms => "ms",
+            OutputFormat::Coe => "coe",
+            OutputFormat::Ihex => "hex",
+            OutputFormat::Bin | OutputFormat::BinaryV2 => "bin",
+            OutputFormat::RustArray => "rs",
+            OutputFormat::Symbols => "sym",
+            OutputFormat::Logisim => "img",
+            OutputFormat::Base64 | OutputFormat::Base64Url => "b64",
+        }
+    }
+
+    //Whether the output is lines of text, which are written with the configured line ending.
+    pub fn is_text(&self) -> bool {
+        !matches!(self, OutputFormat::Bin | OutputFormat::BinaryV2)
+    }
+}
+
+//A file written after assembling, the config file can list several with [[output]].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputTarget {
+    pub format: OutputFormat,
+    pub path: PathBuf,
+    //Only used by the coe format, defaults to binary.
+    pub coe_radix: Option<CoeRadix>,
+}
+
+impl OutputTarget {
+    pub fn writer(&self) -> Box<dyn OutputWriter> {
+        match self.format {
+            OutputFormat::Ms => Box::new(MsWriter),
+            OutputFormat::Coe => Box::new(CoeWriter { radix: self.coe_radix.unwrap_or(CoeRadix::Binary) }),
+            OutputFormat::Ihex => Box::new(IhexWriter),
+            OutputFormat::Bin => Box::new(BinWriter { format: BinaryFormat::V1 }),
+            OutputFormat::BinaryV2 => Box::new(BinWriter { format: BinaryFormat::V2 }),
+            OutputFormat::RustArray => Box::new(RustArrayWriter),
+            OutputFormat::Symbols => Box::new(SymbolsWriter),
+            OutputFormat::Logisim => Box::new(LogisimWriter),
+            OutputFormat::Base64 => Box::new(Base64Writer { url_safe: false }),
+            OutputFormat::Base64Url => Box::new(Base64Writer { url_safe: true }),
+        }
+    }
+}
+
+pub trait OutputWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()>;
+}
+
+//Writes the file next to its path first and renames it over the path once everything has been
+//written, so a failed or interrupted write leaves the old file as it was.
+pub fn atomic_write(path: &Path, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+    let temporary = temporary_path(path);
+
+    File::create(&temporary)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temporary, path))
+        .inspect_err(|_| {
+            fs::remove_file(&temporary).ok();
+        })
+}
+
+//program.ms is written as program.ms.tmp.
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+//Converts each RAM cell to big endian bytes, cells wider than 8 bits take up multiple bytes.
+pub fn to_bytes(machine_code: &[String]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for cell in machine_code {
+        let value = u64::from_str_radix(cell, 2).expect("Machine code must be a binary string.");
+        let num_bytes = cell.len().div_ceil(8);
+        for i in (0..num_bytes).rev() {
+            bytes.push((value >> (i * 8)) as u8);
+        }
+    }
+
+    bytes
+}
@@ -0,0 +1,16 @@
+use std::io::{self, Write};
+
+use crate::assembler::AssembledProgram;
+use crate::output::OutputWriter;
+
+pub struct MsWriter;
+
+impl OutputWriter for MsWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        for s in &program.machine_code {
+            writeln!(out, "{}", s)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use std::io::{self, Write};
+
+use crate::assembler::AssembledProgram;
+use crate::output::{to_bytes, OutputWriter};
+
+//Writes Rust source declaring the program bytes and its marks, meant to be include!-ed.
+pub struct RustArrayWriter;
+
+impl OutputWriter for RustArrayWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        let bytes: Vec<String> = to_bytes(&program.machine_code).iter()
+            .map(|b| format!("{:#04x}", b))
+            .collect();
+
+        let mut marks: Vec<(&String, &usize)> = program.marks.iter().collect();
+        marks.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+
+        let symbols: Vec<String> = marks.iter()
+            .map(|(mark, address)| format!("({:?}, {})", mark, address))
+            .collect();
+
+        writeln!(out, "#[allow(dead_code)]")?;
+        writeln!(out, "pub const PROGRAM: [u8; {}] = [{}];", bytes.len(), bytes.join(", "))?;
+        writeln!(out, "#[allow(dead_code)]")?;
+        writeln!(out, "pub const PROGRAM_SYMBOLS: &[(&str, usize)] = &[{}];", symbols.join(", "))?;
+
+        Ok(())
+    }
+}
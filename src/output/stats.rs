@@ -0,0 +1,14 @@
+use std::io::{self, Write};
+
+use crate::assembler::AssembledProgram;
+use crate::output::OutputWriter;
+use crate::stats::ProgramStats;
+
+//Writes the program stats as JSON.
+pub struct StatsWriter;
+
+impl OutputWriter for StatsWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{}", serde_json::to_string_pretty(&ProgramStats::new(program))?)
+    }
+}
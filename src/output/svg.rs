@@ -0,0 +1,109 @@
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+use crate::assembler::AssembledProgram;
+use crate::instructions::Instructions;
+use crate::output::OutputWriter;
+
+const COLUMNS: usize = 16;
+const CELL_SIZE: usize = 24;
+const MARGIN: usize = 8;
+const LEGEND_HEIGHT: usize = 24;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CellKind {
+    Instruction,
+    //The value of a DATA, in the cell after it.
+    Data,
+    Empty,
+    Mmio,
+}
+
+impl CellKind {
+    fn color(&self) -> &'static str {
+        match self {
+            CellKind::Instruction => "#4caf50",
+            CellKind::Data => "#2196f3",
+            CellKind::Empty => "#d0d0d0",
+            CellKind::Mmio => "#e53935",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            CellKind::Instruction => "instruction",
+            CellKind::Data => "data",
+            CellKind::Empty => "empty",
+            CellKind::Mmio => "mmio",
+        }
+    }
+}
+
+//Draws the RAM as a grid of cells, 16 to a row, colored by what each holds, with the name of
+//each mark over the cell it points to. Cells in the mmio ranges are drawn as memory mapped IO.
+pub struct SvgWriter {
+    //Number of cells drawn, usually the whole address space.
+    pub size: usize,
+    pub mmio: Vec<RangeInclusive<usize>>,
+}
+
+impl SvgWriter {
+    fn cell_kinds(&self, program: &AssembledProgram) -> Vec<CellKind> {
+        let mut kinds: Vec<CellKind> = (0..self.size)
+            .map(|address| match address < program.machine_code.len() {
+                true => CellKind::Instruction,
+                false => CellKind::Empty,
+            })
+            .collect();
+
+        for assembled in &program.instructions {
+            if let Instructions::Data { .. } = assembled.instruction {
+                if let Some(kind) = kinds.get_mut(assembled.address + 1) {
+                    *kind = CellKind::Data;
+                }
+            }
+        }
+
+        for (address, kind) in kinds.iter_mut().enumerate() {
+            if self.mmio.iter().any(|range| range.contains(&address)) {
+                *kind = CellKind::Mmio;
+            }
+        }
+
+        kinds
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+impl OutputWriter for SvgWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        let rows = self.size.div_ceil(COLUMNS);
+        let width = COLUMNS * CELL_SIZE + 2 * MARGIN;
+        let height = rows * CELL_SIZE + 2 * MARGIN + LEGEND_HEIGHT;
+        let position = |address: usize| (MARGIN + address % COLUMNS * CELL_SIZE, MARGIN + address / COLUMNS * CELL_SIZE);
+
+        writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="7">"#, width, height)?;
+
+        for (address, kind) in self.cell_kinds(program).iter().enumerate() {
+            let (x, y) = position(address);
+            writeln!(out, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="white"/>"#, x, y, CELL_SIZE, CELL_SIZE, kind.color())?;
+        }
+
+        for (mark, address) in program.marks.iter().filter(|(_, address)| **address < self.size) {
+            let (x, y) = position(*address);
+            writeln!(out, r#"<text x="{}" y="{}">{}</text>"#, x + 2, y + CELL_SIZE / 2 + 2, escape(mark))?;
+        }
+
+        let legend_y = MARGIN + rows * CELL_SIZE + 8;
+        for (i, kind) in [CellKind::Instruction, CellKind::Data, CellKind::Empty, CellKind::Mmio].iter().enumerate() {
+            let x = MARGIN + i * 6 * CELL_SIZE / 2;
+            writeln!(out, r#"<rect x="{}" y="{}" width="10" height="10" fill="{}"/>"#, x, legend_y, kind.color())?;
+            writeln!(out, r#"<text x="{}" y="{}" font-size="10">{}</text>"#, x + 14, legend_y + 9, kind.label())?;
+        }
+
+        writeln!(out, "</svg>")
+    }
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::assembler::AssembledProgram;
+use crate::error::AssemblerError;
+use crate::output::OutputWriter;
+
+//Writes one "name address" line per mark, sorted by address.
+pub struct SymbolsWriter;
+
+impl OutputWriter for SymbolsWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        let mut marks: Vec<(&String, &usize)> = program.marks.iter().collect();
+        marks.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+
+        for (mark, address) in marks {
+            writeln!(out, "{} {}", mark, address)?;
+        }
+
+        Ok(())
+    }
+}
+
+//Reads a symbol file written by SymbolsWriter.
+pub fn parse_symbols(content: &str) -> Result<HashMap<String, usize>, AssemblerError> {
+    let mut symbols = HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => {}
+            [mark, address] => {
+                let address = address.parse().map_err(|_|
+                    AssemblerError::InvalidSymbol { line: i + 1, text: line.to_string() }
+                )?;
+
+                symbols.insert(mark.to_string(), address);
+            }
+            _ => return Err(AssemblerError::InvalidSymbol { line: i + 1, text: line.to_string() }),
+        }
+    }
+
+    Ok(symbols)
+}
@@ -0,0 +1,60 @@
+use std::io::{self, Write};
+
+use strum::IntoEnumIterator;
+
+use crate::analysis::register_usage;
+use crate::assembler::{AssembledInstruction, AssembledProgram};
+use crate::instructions::Register;
+use crate::mark_name::split_offset;
+use crate::output::OutputWriter;
+
+//Writes which instructions reference each mark and which instructions write and read each register.
+pub struct XRefTableWriter;
+
+fn describe(instructions: &[&AssembledInstruction]) -> String {
+    let descriptions: Vec<String> = instructions.iter()
+        .map(|assembled| format!("{} {}", assembled.line, assembled.instruction.mnemonic()))
+        .collect();
+
+    if descriptions.is_empty() {
+        "-".to_string()
+    } else {
+        descriptions.join(", ")
+    }
+}
+
+impl OutputWriter for XRefTableWriter {
+    fn write(&self, program: &AssembledProgram, out: &mut dyn Write) -> io::Result<()> {
+        let mut marks: Vec<(&String, &usize)> = program.mark_lines.iter().collect();
+        marks.sort();
+
+        writeln!(out, "{:<16} {:<6} References", "Mark", "Line")?;
+        for (mark, line) in marks {
+            let references: Vec<&AssembledInstruction> = program.instructions.iter()
+                .filter(|assembled| assembled.instruction.jump_mark().map(|jump| split_offset(jump).0) == Some(mark.as_str()))
+                .collect();
+
+            writeln!(out, "{:<16} {:<6} {}", mark, line, describe(&references))?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "{:<16} {:<30} Read", "Register", "Written")?;
+        for reg in Register::iter() {
+            let mut written = Vec::new();
+            let mut read = Vec::new();
+            for assembled in &program.instructions {
+                let (reads, writes) = register_usage(&assembled.instruction);
+                if writes.contains(&reg) {
+                    written.push(assembled);
+                }
+                if reads.contains(&reg) {
+                    read.push(assembled);
+                }
+            }
+
+            writeln!(out, "{:<16} {:<30} {}", reg, describe(&written), describe(&read))?;
+        }
+
+        Ok(())
+    }
+}
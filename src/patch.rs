@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::assembler::{encode_instruction, parse_line, ParseContext};
+use crate::config::Config;
+use crate::disassembler::disassemble;
+use crate::error::AssemblerError;
+use crate::warning::Warning;
+
+pub struct PatchedProgram {
+    //One binary string per RAM cell.
+    pub machine_code: Vec<String>,
+    pub warnings: Vec<Warning>,
+}
+
+//Moves the patch to the address given by a .org directive.
+fn parse_org(words: &[&str], ctx: &mut ParseContext, max_num_ram_cells: usize) -> Result<(), AssemblerError> {
+    let line = ctx.real_line_number;
+
+    let address =
+        match words {
+            [_, address] => address,
+            _ => return Err(AssemblerError::InvalidFormatting { line }),
+        };
+
+    match address.parse::<usize>() {
+        Ok(value) if value < max_num_ram_cells => {
+            ctx.machine_code_line_number = value as i32 - 1;
+            Ok(())
+        }
+        _ => Err(AssemblerError::InvalidAddress { line, address: address.to_string(), maximum: max_num_ram_cells }),
+    }
+}
+
+//Assembles the patch source and writes it over the original machine code. The patch is placed
+//with .org ADDRESS lines, every address it does not write keeps its original value.
+pub fn apply_patch(original: &[String], patch: &str, config: &Config) -> Result<PatchedProgram, AssemblerError> {
+    let max_num_ram_cells = usize::pow(2, config.address_bits as u32);
+
+    let mut ctx = ParseContext::new(config);
+    let mut instructions = Vec::new();
+    for line in patch.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        if words.first() == Some(&".org") {
+            ctx.real_line_number += 1;
+            parse_org(&words, &mut ctx, max_num_ram_cells)?;
+            continue;
+        }
+
+        let mut address = (ctx.machine_code_line_number + 1) as usize;
+        for instruction in parse_line(line, &mut ctx)? {
+            let size = instruction.size(config.word_bits);
+            instructions.push((ctx.real_line_number, address, instruction));
+            address += size;
+        }
+    }
+
+    let mut marks = ctx.marks_to_machine_code.clone();
+    //Jump targets that are plain numbers are addresses in the original program, as written by the
+    //disassembler.
+    for (_, _, instruction) in &instructions {
+        if let Some(mark) = instruction.jump_mark() {
+            if let Ok(address) = mark.parse::<i32>() {
+                marks.entry(mark.to_string()).or_insert(address);
+            }
+        }
+    }
+
+    let mut machine_code = original.to_vec();
+    let mut written_by: HashMap<usize, usize> = HashMap::new();
+    let mut warnings = Vec::new();
+    for (line, address, instruction) in instructions {
+        let binary_string = encode_instruction(instruction, &marks, config)?;
+
+        for (offset, cell) in binary_string.lines().enumerate() {
+            let cell_address = address + offset;
+
+            if cell_address >= max_num_ram_cells {
+                return Err(AssemblerError::TooManyInstructions { found: cell_address + 1, maximum: max_num_ram_cells });
+            }
+
+            if let Some(previous_line) = written_by.insert(cell_address, line) {
+                warnings.push(Warning::OverlappingPatch { line, address: cell_address, previous_line });
+            }
+
+            //Writing past the end of the original grows it with zeroed cells.
+            if cell_address >= machine_code.len() {
+                machine_code.resize(cell_address + 1, "0".repeat(config.word_bits));
+            }
+
+            machine_code[cell_address] = cell.to_string();
+        }
+    }
+
+    warnings.retain(|warning| !config.disabled_warnings.contains(&warning.kind()));
+
+    Ok(PatchedProgram { machine_code, warnings })
+}
+
+//Writes a patch that turns the original machine code into the modified machine code when given
+//to apply_patch. Changed instructions next to each other share a single .org line.
+pub fn create_patch(original: &[String], modified: &[String]) -> Result<String, AssemblerError> {
+    let instructions = disassemble(modified, None)?;
+
+    let mut patch = String::new();
+    let mut next_address = None;
+    for (index, (address, instruction)) in instructions.iter().enumerate() {
+        let end =
+            match instructions.get(index + 1) {
+                Some((next, _)) => *next,
+                None => modified.len(),
+            };
+
+        let changed = (*address..end).any(|cell_address| original.get(cell_address) != modified.get(cell_address));
+        if !changed {
+            continue;
+        }
+
+        if next_address != Some(*address) {
+            patch.push_str(&format!(".org {}\n", address));
+        }
+
+        patch.push_str(&format!("{}\n", instruction));
+        next_address = Some(end);
+    }
+
+    Ok(patch)
+}
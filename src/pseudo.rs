@@ -0,0 +1,251 @@
+//Pseudo instructions, each expands into several built in instructions. Like the ALU
+//instructions, the result is placed in the second register.
+//
+//  XNOR RA RB    XOR RA RB, NOT RB RB
+//  NAND RA RB    AND RA RB, NOT RB RB
+//  NOR RA RB     OR RA RB, NOT RB RB
+//
+//Rotates shift RA into RB and then move the bit that was shifted out into the other end of RB
+//using the scratch register, so RB can not be the scratch register. N is different for every
+//mark the assembler makes.
+//
+//  ROL RA RB     CLF
+//                SHL RA RB
+//                JIF C __internal_rol_carry_N
+//                JMP __internal_rol_done_N
+//                MARK __internal_rol_carry_N
+//                DATA scratch 1
+//                OR scratch RB
+//                MARK __internal_rol_done_N
+//
+//  ROR RA RB     The same as ROL using SHR and __internal_ror_ marks, with DATA scratch set to
+//                only the highest bit of a RAM cell.
+//
+//An arithmetic shift right keeps the sign bit of RA, found by shifting it out into the carry
+//flag. Neither register can be the scratch register. The 128 is the highest bit of an 8 bit RAM
+//cell.
+//
+//  SAR RA RB     CLF
+//                SHL RA scratch
+//                DATA scratch 128
+//                JIF C __internal_sar_shift_N
+//                DATA scratch 0
+//                MARK __internal_sar_shift_N
+//                CLF
+//                SHR RA RB
+//                OR scratch RB
+//
+//A bit test shifts the bit out of RA and into the carry flag, ready for a JIF C. RA is changed
+//and BIT must be below the number of bits in a RAM cell.
+//
+//  BT RA BIT     SHR RA RA, repeated BIT + 1 times
+//
+//Setting and clearing a bit loads a mask into the scratch register, so RA can not be the
+//scratch register.
+//
+//  BSET RA BIT   DATA scratch 1 << BIT
+//                OR scratch RA
+//  BCLR RA BIT   DATA scratch every bit except 1 << BIT
+//                AND scratch RA
+//
+//The distance to a mark is counted from the address of the PCREL itself, so code that loads a
+//mark this way still works when it is placed somewhere else in RAM. It is a signed byte.
+//
+//  PCREL RA MARK DATA RA address of MARK - address of PCREL
+
+use crate::assembler::{fresh_mark, ParseContext};
+use crate::error::AssemblerError;
+use crate::instructions::{Instructions, Register};
+
+//Mnemonic and description of every pseudo instruction.
+pub const PSEUDO_INSTRUCTIONS: &[(&str, &str)] = &[
+    ("XNOR", "XNOR RA and RB, put answer in RB."),
+    ("NAND", "NAND RA and RB, put answer in RB."),
+    ("NOR", "NOR RA and RB, put answer in RB."),
+    ("ROL", "Rotate RA left, put answer in RB."),
+    ("ROR", "Rotate RA right, put answer in RB."),
+    ("SAR", "Shift RA right keeping its sign bit, put answer in RB."),
+    ("BT", "Move bit BIT of RA into the carry flag, RA is changed."),
+    ("BSET", "Set bit BIT of RA."),
+    ("BCLR", "Clear bit BIT of RA."),
+    ("PCREL", "Load the distance from this instruction to MARK into RA."),
+];
+
+fn two_registers(words: &[&str], ctx: &ParseContext) -> Result<(Register, Register), AssemblerError> {
+    if words.len() != 3 {
+        return Err(AssemblerError::InvalidFormatting { line: ctx.real_line_number });
+    }
+
+    let reg_a = ctx.register(words[1])?;
+    let reg_b = ctx.register(words[2])?;
+
+    Ok((reg_a, reg_b))
+}
+
+//An ALU instruction followed by a NOT of its result.
+fn inverted(words: &[&str], ctx: &ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    let (reg_a, reg_b) = two_registers(words, ctx)?;
+
+    let instruction =
+        match words[0] {
+            "XNOR" => Instructions::XOr { reg_a, reg_b: reg_b.clone() },
+            "NAND" => Instructions::And { reg_a, reg_b: reg_b.clone() },
+            _ => Instructions::Or { reg_a, reg_b: reg_b.clone() },
+        };
+
+    Ok(vec![instruction, Instructions::Not { reg_a: reg_b.clone(), reg_b }])
+}
+
+//A shift of RA into RB that carries the shifted out bit around to the other end of RB.
+fn rotate(words: &[&str], ctx: &mut ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    let (reg_a, reg_b) = two_registers(words, ctx)?;
+    let scratch = ctx.config.scratch_reg.clone();
+
+    if reg_b == scratch {
+        return Err(AssemblerError::ScratchRegisterUsed { line: ctx.real_line_number, register: reg_b });
+    }
+
+    let (prefix, shift, carried_bit) =
+        match words[0] {
+            "ROL" => ("rol", Instructions::Shl { reg_a, reg_b: reg_b.clone() }, 1),
+            _ => ("ror", Instructions::Shr { reg_a, reg_b: reg_b.clone() }, 1 << (ctx.config.word_bits - 1)),
+        };
+
+    let carry_mark = fresh_mark(&format!("{}_carry", prefix), ctx);
+    let done_mark = fresh_mark(&format!("{}_done", prefix), ctx);
+
+    let start = ctx.machine_code_line_number + 1;
+    let jumps = vec![
+        Instructions::ClearFlags,
+        shift,
+        Instructions::JumpIf { carry: true, a_larger: false, equal: false, zero: false, mark: carry_mark.clone() },
+        Instructions::JumpAddress { mark: done_mark.clone() },
+    ];
+    let carry = vec![
+        Instructions::Data { reg: scratch.clone(), data: carried_bit },
+        Instructions::Or { reg_a: scratch, reg_b },
+    ];
+
+    let carry_address = start + cell_count(&jumps, ctx) as i32;
+    let done_address = carry_address + cell_count(&carry, ctx) as i32;
+    ctx.define_mark(&carry_mark, carry_address);
+    ctx.define_mark(&done_mark, done_address);
+
+    Ok(jumps.into_iter().chain(carry).collect())
+}
+
+//A shift right of RA into RB that keeps the highest bit of RA.
+fn arithmetic_shift(words: &[&str], ctx: &mut ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    let (reg_a, reg_b) = two_registers(words, ctx)?;
+    let scratch = ctx.config.scratch_reg.clone();
+
+    for reg in [&reg_a, &reg_b] {
+        if *reg == scratch {
+            return Err(AssemblerError::ScratchRegisterUsed { line: ctx.real_line_number, register: reg.clone() });
+        }
+    }
+
+    let shift_mark = fresh_mark("sar_shift", ctx);
+
+    let start = ctx.machine_code_line_number + 1;
+    let sign = vec![
+        Instructions::ClearFlags,
+        Instructions::Shl { reg_a: reg_a.clone(), reg_b: scratch.clone() },
+        Instructions::Data { reg: scratch.clone(), data: 1 << (ctx.config.word_bits - 1) },
+        Instructions::JumpIf { carry: true, a_larger: false, equal: false, zero: false, mark: shift_mark.clone() },
+        Instructions::Data { reg: scratch.clone(), data: 0 },
+    ];
+    let shift = vec![
+        Instructions::ClearFlags,
+        Instructions::Shr { reg_a, reg_b: reg_b.clone() },
+        Instructions::Or { reg_a: scratch, reg_b },
+    ];
+
+    ctx.define_mark(&shift_mark, start + cell_count(&sign, ctx) as i32);
+
+    Ok(sign.into_iter().chain(shift).collect())
+}
+
+fn parse_bit(word: &str, ctx: &ParseContext) -> Result<usize, AssemblerError> {
+    match word.parse::<usize>() {
+        Ok(bit) if bit < ctx.config.word_bits => Ok(bit),
+        _ => Err(AssemblerError::BitOutOfRange { line: ctx.real_line_number, bit: word.to_string(), word_bits: ctx.config.word_bits }),
+    }
+}
+
+//Shifts a single bit of a register into the carry flag.
+fn bit_test(words: &[&str], ctx: &ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    if words.len() != 3 {
+        return Err(AssemblerError::InvalidFormatting { line: ctx.real_line_number });
+    }
+
+    let reg = ctx.register(words[1])?;
+    let bit = parse_bit(words[2], ctx)?;
+
+    Ok(vec![Instructions::Shr { reg_a: reg.clone(), reg_b: reg }; bit + 1])
+}
+
+//Sets or clears a single bit of a register with a mask made at assemble time.
+fn bit_mask(words: &[&str], ctx: &ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    if words.len() != 3 {
+        return Err(AssemblerError::InvalidFormatting { line: ctx.real_line_number });
+    }
+
+    let reg = ctx.register(words[1])?;
+    let bit = parse_bit(words[2], ctx)?;
+    let scratch = ctx.config.scratch_reg.clone();
+
+    if reg == scratch {
+        return Err(AssemblerError::ScratchRegisterUsed { line: ctx.real_line_number, register: reg });
+    }
+
+    let all_bits = 1usize.checked_shl(ctx.config.word_bits as u32).map_or(usize::MAX, |bits| bits - 1);
+    let instructions =
+        match words[0] {
+            "BSET" => vec![
+                Instructions::Data { reg: scratch.clone(), data: 1 << bit },
+                Instructions::Or { reg_a: scratch, reg_b: reg },
+            ],
+            _ => vec![
+                Instructions::Data { reg: scratch.clone(), data: all_bits & !(1 << bit) },
+                Instructions::And { reg_a: scratch, reg_b: reg },
+            ],
+        };
+
+    Ok(instructions)
+}
+
+//The DATA is written once every mark has an address.
+fn pc_relative(words: &[&str], ctx: &ParseContext) -> Result<Vec<Instructions>, AssemblerError> {
+    if words.len() != 3 {
+        return Err(AssemblerError::InvalidFormatting { line: ctx.real_line_number });
+    }
+
+    let reg = ctx.register(words[1])?;
+
+    Ok(vec![Instructions::PcRelative { reg, mark: words[2].to_string() }])
+}
+
+fn cell_count(instructions: &[Instructions], ctx: &ParseContext) -> usize {
+    instructions.iter()
+        .map(|instruction| instruction.size(ctx.config.word_bits))
+        .sum()
+}
+
+//Returns None when the words are not a pseudo instruction.
+pub fn expand(words: &[&str], ctx: &mut ParseContext) -> Result<Option<Vec<Instructions>>, AssemblerError> {
+    let instructions =
+        match words[0] {
+            "XNOR" | "NAND" | "NOR" => inverted(words, ctx)?,
+            "ROL" | "ROR" => rotate(words, ctx)?,
+            "SAR" => arithmetic_shift(words, ctx)?,
+            "BT" => bit_test(words, ctx)?,
+            "BSET" | "BCLR" => bit_mask(words, ctx)?,
+            "PCREL" => pc_relative(words, ctx)?,
+            _ => return Ok(None),
+        };
+
+    ctx.machine_code_line_number += cell_count(&instructions, ctx) as i32;
+
+    Ok(Some(instructions))
+}
@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+use logical_cpu_assembler::assembler::{encode_instruction, parse_line, ParseContext};
+use logical_cpu_assembler::config::Config;
+use logical_cpu_assembler::disassembler::disassemble;
+use logical_cpu_assembler::editor::completions;
+use logical_cpu_assembler::instructions::{data_truncated, Instructions};
+use logical_cpu_assembler::mark_name::mark_address;
+use logical_cpu_assembler::output::ms::MsWriter;
+use logical_cpu_assembler::output::{atomic_write, OutputWriter};
+use logical_cpu_assembler::{assemble_str, AssembledProgram, AssemblerError};
+
+const HELP: &str = "\
+Enter an instruction to encode it. Commands:
+  :list               Show the current program.
+  :disassemble        Show the current program disassembled.
+  :save <file.ms>     Write the current program to a file.
+  :reset              Clear the current program.
+  :help               Show this message.";
+
+//Completes the line being typed using the lines already entered, so marks defined earlier in
+//the session are offered.
+struct ReplHelper {
+    source: String,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let source = format!("{}\n{}", self.source, line);
+        let offset = self.source.len() + 1 + pos;
+
+        let start = line[..pos].rfind(char::is_whitespace).map_or(0, |space| space + 1);
+        let candidates = completions(&source, offset).into_iter()
+            .map(|completion| Pair { display: completion.text.clone(), replacement: completion.text })
+            .collect();
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+struct Session<'a> {
+    config: &'a Config,
+    ctx: ParseContext<'a>,
+    //Address of each accepted line, None for lines such as MARK that take no RAM.
+    lines: Vec<(Option<i32>, String)>,
+}
+
+impl<'a> Session<'a> {
+    fn new(config: &'a Config) -> Self {
+        Session { config, ctx: ParseContext::new(config), lines: Vec::new() }
+    }
+
+    fn source(&self) -> String {
+        let lines: Vec<&str> = self.lines.iter().map(|(_, line)| line.as_str()).collect();
+        lines.join("\n")
+    }
+
+    fn assemble(&self) -> Result<AssembledProgram, AssemblerError> {
+        assemble_str(&self.source(), self.config)
+    }
+
+    fn enter(&mut self, line: &str) {
+        let address = self.ctx.machine_code_line_number + 1;
+
+        //Failed lines are not part of the session, so they must not change the parse state.
+        let mut ctx = self.ctx.clone();
+        let instructions =
+            match parse_line(line, &mut ctx) {
+                Ok(instructions) => instructions,
+                Err(e) => {
+                    println!("Error: {}", e);
+                    return;
+                }
+            };
+
+        for warning in &ctx.warnings[self.ctx.warnings.len()..] {
+            println!("Warning: {}", warning);
+        }
+
+        self.ctx = ctx;
+
+        if instructions.is_empty() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if let ["MARK", mark] = words.as_slice() {
+                println!("Mark {} @ address {}", mark, address);
+            }
+            self.lines.push((None, line.to_string()));
+            return;
+        }
+
+        //Pseudo instructions expand into several instructions placed one after another.
+        let mut instruction_address = address;
+        for instruction in instructions {
+            let size = instruction.size(self.config.word_bits) as i32;
+            self.print_encoding(instruction, instruction_address);
+            instruction_address += size;
+        }
+        self.lines.push((Some(address), line.to_string()));
+    }
+
+    fn print_encoding(&self, instruction: Instructions, address: i32) {
+        let pending_mark = instruction.referenced_mark()
+            .filter(|mark| mark_address(mark, &self.ctx.marks_to_machine_code).is_none())
+            .map(|mark| mark.to_string());
+
+        match pending_mark {
+            //The jump can still be resolved once the mark is defined later in the session.
+            Some(mark) => {
+                let opcode = Instructions::binary(instruction, self.config.word_bits);
+                println!("Encoded: {} <{}> @ address {} (mark not defined yet)", opcode, mark, address);
+            }
+            None => {
+                let instruction =
+                    match instruction {
+                        Instructions::PcRelative { reg, mark } => {
+                            let offset = mark_address(&mark, &self.ctx.marks_to_machine_code).unwrap_or_default() - address as i64;
+                            Instructions::Data { reg, data: data_truncated(offset as usize, self.config.word_bits) }
+                        }
+                        instruction => instruction,
+                    };
+
+                match encode_instruction(instruction, &self.ctx.marks_to_machine_code, self.config) {
+                    Ok(binary) => println!("Encoded: {} @ address {}", binary.replace('\n', " "), address),
+                    Err(e) => println!("Error: {}", e),
+                }
+            }
+        }
+    }
+
+    fn list(&self) {
+        for (address, line) in &self.lines {
+            match address {
+                Some(address) => println!("{:>4}  {}", address, line),
+                None => println!("      {}", line),
+            }
+        }
+    }
+
+    fn disassemble(&self) -> Result<(), AssemblerError> {
+        let program = self.assemble()?;
+        for (address, instruction) in disassemble(&program.machine_code, None)? {
+            println!("{:>4}  {}", address, instruction);
+        }
+
+        Ok(())
+    }
+
+    fn save(&self, file_name: &str) -> Result<(), AssemblerError> {
+        let program = self.assemble()?;
+
+        atomic_write(Path::new(file_name), |file| MsWriter.write(&program, file))
+            .map_err(|error| AssemblerError::Io { path: file_name.into(), error })?;
+
+        println!("Saved {} RAM cells to {}.", program.machine_code.len(), file_name);
+        Ok(())
+    }
+
+    fn command(&mut self, command: &str) {
+        let words: Vec<&str> = command.split_whitespace().collect();
+
+        let result =
+            match words.as_slice() {
+                [":list"] => {
+                    self.list();
+                    Ok(())
+                }
+                [":disassemble"] => self.disassemble(),
+                [":save", file_name] => self.save(file_name),
+                [":reset"] => {
+                    *self = Session::new(self.config);
+                    Ok(())
+                }
+                [":help"] => {
+                    println!("{}", HELP);
+                    Ok(())
+                }
+                _ => {
+                    println!("Unknown command {}, try :help.", command);
+                    Ok(())
+                }
+            };
+
+        if let Err(e) = result {
+            println!("Error: {}", e);
+        }
+    }
+}
+
+pub fn run(config: &Config) -> rustyline::Result<()> {
+    let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+    let mut session = Session::new(config);
+    editor.set_helper(Some(ReplHelper { source: String::new() }));
+
+    println!("{}", HELP);
+
+    loop {
+        match editor.readline("> ") {
+            Ok(line) => {
+                editor.add_history_entry(line.as_str())?;
+
+                if line.trim_start().starts_with(':') {
+                    session.command(line.trim());
+                } else {
+                    session.enter(&line);
+                }
+
+                if let Some(helper) = editor.helper_mut() {
+                    helper.source = session.source();
+                }
+            }
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//Programs built into the assembler with the machine code they are known to assemble into, to
+//check that an installed assembler works.
+
+use std::fmt;
+
+use crate::assembler::assemble_str;
+use crate::config::Config;
+use crate::output::ms::MsWriter;
+use crate::output::OutputWriter;
+
+pub struct SelfTest {
+    pub name: &'static str,
+    pub source: &'static str,
+    //The program in the ms format.
+    pub expected: &'static [u8],
+}
+
+macro_rules! self_test {
+    ($name:literal) => {
+        SelfTest {
+            name: $name,
+            source: include_str!(concat!("self_test/", $name, ".asm")),
+            expected: include_bytes!(concat!("self_test/", $name, ".ms")),
+        }
+    };
+}
+
+pub const SELF_TESTS: [SelfTest; 5] = [
+    self_test!("instructions"),
+    self_test!("marks"),
+    self_test!("jump_if"),
+    self_test!("data"),
+    self_test!("multiplication"),
+];
+
+#[derive(Debug, PartialEq)]
+pub enum SelfTestResult {
+    Passed,
+    Error(String),
+    //Every RAM cell that differs as (index, expected, found), a missing cell is empty.
+    Mismatch(Vec<(usize, String, String)>),
+}
+
+impl fmt::Display for SelfTestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelfTestResult::Passed => write!(f, "passed"),
+            SelfTestResult::Error(message) => write!(f, "failed to assemble, {}", message),
+            SelfTestResult::Mismatch(cells) => {
+                write!(f, "machine code differs")?;
+                for (index, expected, found) in cells {
+                    write!(f, "\n  cell {}: expected {:<8} found {}", index, expected, found)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+//Assembles with the default config, the expected machine code was written with it.
+pub fn run_self_test(test: &SelfTest) -> SelfTestResult {
+    let program =
+        match assemble_str(test.source, &Config::default()) {
+            Ok(program) => program,
+            Err(error) => return SelfTestResult::Error(error.to_string()),
+        };
+
+    let mut found = Vec::new();
+    if let Err(error) = MsWriter.write(&program, &mut found) {
+        return SelfTestResult::Error(error.to_string());
+    }
+
+    if found == test.expected {
+        return SelfTestResult::Passed;
+    }
+
+    let expected: Vec<&str> = std::str::from_utf8(test.expected).unwrap_or_default().lines().collect();
+    let found: Vec<&str> = std::str::from_utf8(&found).unwrap_or_default().lines().collect();
+
+    let cells = (0..expected.len().max(found.len()))
+        .filter_map(|i| {
+            let expected = expected.get(i).copied().unwrap_or_default();
+            let found = found.get(i).copied().unwrap_or_default();
+            (expected != found).then(|| (i, expected.to_string(), found.to_string()))
+        })
+        .collect();
+
+    SelfTestResult::Mismatch(cells)
+}
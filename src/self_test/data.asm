@@ -0,0 +1,6 @@
+# DATA with the smallest, the largest signed and the largest unsigned byte.
+DATA R0 0
+DATA R1 127
+DATA R2 255
+DATA R3 0
+END
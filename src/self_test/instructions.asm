@@ -0,0 +1,18 @@
+# Every instruction with a different pair of registers.
+DATA R0 1
+DATA R1 2
+ADD R0 R1
+SHR R1 R2
+SHL R2 R3
+NOT R3 R0
+AND R0 R1
+OR R1 R2
+XOR R2 R3
+ST R0 R1
+LD R1 R0
+CLF
+DATA R2 19
+JMPR R2
+JMP last
+MARK last
+END
@@ -0,0 +1,18 @@
+# JIF with every combination of the C, A, E and Z flags.
+MARK top
+JIF C top
+JIF A top
+JIF E top
+JIF Z top
+JIF CA top
+JIF CE top
+JIF CZ top
+JIF AE top
+JIF AZ top
+JIF EZ top
+JIF CAE top
+JIF CAZ top
+JIF CEZ top
+JIF AEZ top
+JIF CAEZ top
+END
@@ -0,0 +1,11 @@
+# Jumps forward and backward to marks, including two marks at the same address.
+JMP forward
+MARK backward
+DATA R0 3
+JMP done
+MARK forward
+MARK also_forward
+JMP backward
+MARK done
+JMP also_forward
+END
@@ -0,0 +1,23 @@
+# Multiplies 5 by 5 by shifting and adding, leaving the answer in R1.
+DATA R0 5
+DATA R1 5
+DATA R3 1
+XOR R2 R2
+
+MARK start
+CLF
+SHR R0 R0
+JIF C first
+JMP second
+MARK first
+CLF
+ADD R1 R2
+MARK second
+CLF
+SHL R1 R1
+SHL R3 R3
+JIF C end
+JMP start
+
+MARK end
+END
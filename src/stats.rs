@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{self, Write};
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::assembler::AssembledProgram;
+use crate::flow::{cyclomatic_complexity, FlowGraph};
+use crate::instructions::Instructions;
+use crate::output::to_bytes;
+
+//Size and content of an assembled program, written as JSON by --stats-output.
+#[derive(Debug, Serialize)]
+pub struct ProgramStats {
+    pub code_size_bytes: usize,
+    //Number of times each mnemonic is used, pseudo instructions are counted by what they expand to.
+    pub instruction_counts: BTreeMap<String, usize>,
+    pub mark_count: usize,
+    pub estimated_cycles: usize,
+    //Share of the budget used, or of the RAM when no budget is set.
+    pub budget_used_percent: f64,
+    pub passed_budget: bool,
+    pub cyclomatic_complexity: u32,
+    pub branch_count: usize,
+}
+
+pub fn percentage(used: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+
+    used as f64 * 100.0 / total as f64
+}
+
+impl ProgramStats {
+    pub fn new(program: &AssembledProgram) -> Self {
+        let mut instruction_counts = BTreeMap::new();
+        for assembled in &program.instructions {
+            *instruction_counts.entry(assembled.instruction.mnemonic().to_string()).or_insert(0) += 1;
+        }
+
+        let used = program.machine_code.len();
+        let budget = program.budget.unwrap_or(usize::pow(2, program.address_bits as u32));
+
+        let instructions: Vec<(usize, Instructions)> = program.instructions.iter()
+            .map(|assembled| (assembled.address, assembled.instruction.clone()))
+            .collect();
+        let complexity = ComplexityReport::new(&instructions, &program.marks);
+
+        ProgramStats {
+            code_size_bytes: to_bytes(&program.machine_code).len(),
+            instruction_counts,
+            mark_count: program.marks.len(),
+            estimated_cycles: program.estimated_cycles(),
+            budget_used_percent: percentage(used, budget),
+            passed_budget: used <= budget,
+            cyclomatic_complexity: complexity.cyclomatic_complexity,
+            branch_count: complexity.branch_count,
+        }
+    }
+}
+
+//Above this a program is hard to follow as a whole.
+pub const COMPLEXITY_LIMIT: u32 = 10;
+
+#[derive(Debug, Serialize)]
+pub struct ComplexityReport {
+    pub cyclomatic_complexity: u32,
+    pub branch_count: usize,
+    pub instruction_count: usize,
+    //Different addresses jumped to when the marks are not known, such as in machine code.
+    pub mark_count: usize,
+}
+
+impl ComplexityReport {
+    pub fn new(instructions: &[(usize, Instructions)], marks: &IndexMap<String, usize>) -> Self {
+        let mark_count =
+            match marks.is_empty() {
+                true => instructions.iter().filter_map(|(_, instruction)| instruction.jump_mark()).collect::<BTreeSet<_>>().len(),
+                false => marks.len(),
+            };
+
+        ComplexityReport {
+            cyclomatic_complexity: cyclomatic_complexity(&FlowGraph::new(instructions, marks)),
+            branch_count: instructions.iter().filter(|(_, instruction)| instruction.is_branch()).count(),
+            instruction_count: instructions.len(),
+            mark_count,
+        }
+    }
+}
+
+impl fmt::Display for ComplexityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cyclomatic complexity: {}", self.cyclomatic_complexity)?;
+        writeln!(f, "Branches: {}", self.branch_count)?;
+        writeln!(f, "Instructions: {}", self.instruction_count)?;
+        write!(f, "Marks: {}", self.mark_count)?;
+
+        if self.cyclomatic_complexity > COMPLEXITY_LIMIT {
+            write!(f, "\nComplexity is above {}, consider splitting the program into subroutines.", COMPLEXITY_LIMIT)?;
+        }
+
+        Ok(())
+    }
+}
+
+//Longest bar drawn by write_histogram.
+const HISTOGRAM_WIDTH: usize = 40;
+
+#[derive(Debug, Serialize)]
+pub struct HistogramEntry {
+    pub mnemonic: String,
+    pub count: usize,
+    //Share of all the instructions.
+    pub percent: f64,
+}
+
+//Number of times each mnemonic is used, the most used first and ties in alphabetical order.
+pub fn instruction_histogram(instructions: &[Instructions]) -> Vec<HistogramEntry> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for instruction in instructions {
+        *counts.entry(instruction.mnemonic()).or_insert(0) += 1;
+    }
+
+    let mut histogram: Vec<HistogramEntry> = counts.into_iter()
+        .map(|(mnemonic, count)| HistogramEntry {
+            mnemonic: mnemonic.to_string(),
+            count,
+            percent: percentage(count, instructions.len()),
+        })
+        .collect();
+    histogram.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+
+    histogram
+}
+
+//One bar per mnemonic, scaled so the most used one is HISTOGRAM_WIDTH long.
+pub fn write_histogram(histogram: &[HistogramEntry], out: &mut dyn Write) -> io::Result<()> {
+    let max_count = histogram.first().map_or(0, |entry| entry.count).max(1);
+    let mnemonic_width = histogram.iter().map(|entry| entry.mnemonic.len()).max().unwrap_or(0);
+
+    for entry in histogram {
+        let bar = "\u{2588}".repeat((entry.count * HISTOGRAM_WIDTH).div_ceil(max_count));
+        writeln!(out, "{:<width$} | {} {} ({:.1}%)", entry.mnemonic, bar, entry.count, entry.percent, width = mnemonic_width)?;
+    }
+
+    Ok(())
+}
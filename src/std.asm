@@ -0,0 +1,83 @@
+# Standard library of macros, included with .include <std>.
+#
+# Every macro except SWAP overwrites the scratch register (R3 unless set with --scratch-reg), so none of its
+# operands may be the scratch register. Counts are checked before each step, so a count of 0
+# does nothing.
+
+# MEMCOPY SRC DST LEN
+# Copies LEN RAM cells starting at the address in SRC to the cells starting at the address in
+# DST. SRC and DST are left just past the cells copied and LEN is left at 0.
+.macro MEMCOPY src dst len
+MARK __memcopy_\@_loop
+OR \len \len
+JIF Z __memcopy_\@_done
+LD \src \scratch
+ST \scratch \dst
+DATA \scratch 1
+CLF
+ADD \scratch \src
+CLF
+ADD \scratch \dst
+DATA \scratch 0
+NOT \scratch \scratch
+CLF
+ADD \scratch \len
+JMP __memcopy_\@_loop
+MARK __memcopy_\@_done
+.endm
+
+# MEMSET DST VAL LEN
+# Stores VAL in LEN RAM cells starting at the address in DST. DST is left just past the cells
+# set and LEN is left at 0.
+.macro MEMSET dst val len
+MARK __memset_\@_loop
+OR \len \len
+JIF Z __memset_\@_done
+ST \val \dst
+DATA \scratch 1
+CLF
+ADD \scratch \dst
+DATA \scratch 0
+NOT \scratch \scratch
+CLF
+ADD \scratch \len
+JMP __memset_\@_loop
+MARK __memset_\@_done
+.endm
+
+# DELAY_CYCLES COUNT
+# Loops COUNT times doing nothing, each loop runs 7 instructions. COUNT is left at 0.
+.macro DELAY_CYCLES count
+MARK __delay_\@_loop
+OR \count \count
+JIF Z __delay_\@_done
+DATA \scratch 0
+NOT \scratch \scratch
+CLF
+ADD \scratch \count
+JMP __delay_\@_loop
+MARK __delay_\@_done
+.endm
+
+# SWAP A B
+# Swaps the values of A and B with three XORs, A and B must be different registers.
+.macro SWAP a b
+XOR \a \b
+XOR \b \a
+XOR \a \b
+.endm
+
+# ABS REG
+# Replaces the value in REG with its absolute value, reading it as a two's complement number.
+.macro ABS reg
+CLF
+SHL \reg \scratch
+JIF C __abs_\@_negative
+JMP __abs_\@_done
+MARK __abs_\@_negative
+NOT \reg \reg
+DATA \scratch 1
+CLF
+ADD \scratch \reg
+MARK __abs_\@_done
+.endm
@@ -0,0 +1,171 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::output::listing::ListingWriter;
+use crate::output::{to_bytes, OutputWriter};
+use crate::{assemble_str, AssembledProgram, SimulatorInterface, StepOutcome};
+
+//Separates the source of an .asmtest file from the machine code it should assemble to.
+pub const FIXTURE_SEPARATOR: &str = "---";
+
+//Set to 1 to write the assembled output over the golden files instead of comparing them.
+pub const UPDATE_GOLDENS: &str = "UPDATE_GOLDENS";
+
+#[derive(Debug, PartialEq)]
+pub enum TestResult {
+    Passed,
+    //Line of the expected machine code, counting from 1, with both values. A missing cell is an
+    //empty string.
+    Mismatch { line: usize, expected: String, found: String },
+    Error(String),
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestResult::Passed => write!(f, "passed"),
+            TestResult::Mismatch { line, expected, found } => {
+                write!(f, "line {} of the expected output is {:?}, assembled {:?}", line, expected, found)
+            }
+            TestResult::Error(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+//Assembles the source of an .asmtest file with the default config and compares it with the
+//expected machine code below the separator, one RAM cell per line.
+pub fn run_fixture(path: &Path) -> TestResult {
+    let content =
+        match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => return TestResult::Error(format!("Unable to read {}: {}", path.display(), e)),
+        };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let separator =
+        match lines.iter().position(|line| line.trim() == FIXTURE_SEPARATOR) {
+            Some(separator) => separator,
+            None => return TestResult::Error(format!("No {} line between the source and the expected output.", FIXTURE_SEPARATOR)),
+        };
+
+    let source = lines[..separator].join("\n");
+    let expected: Vec<&str> = lines[separator + 1..].iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let program =
+        match assemble_str(&source, &Config::default()) {
+            Ok(program) => program,
+            Err(e) => return TestResult::Error(e.to_string()),
+        };
+
+    for i in 0..expected.len().max(program.machine_code.len()) {
+        let expected_cell = expected.get(i).copied().unwrap_or_default();
+        let found_cell = program.machine_code.get(i).map(|cell| cell.as_str()).unwrap_or_default();
+
+        if expected_cell != found_cell {
+            return TestResult::Mismatch { line: i + 1, expected: expected_cell.to_string(), found: found_cell.to_string() };
+        }
+    }
+
+    TestResult::Passed
+}
+
+//Loads the program into the simulator and steps it until END, returning the number of steps
+//taken. Fails when an instruction is not known or END is not reached within max_steps.
+pub fn run_program(simulator: &mut dyn SimulatorInterface, program: &AssembledProgram, max_steps: usize) -> Result<usize, String> {
+    simulator.load_binary(&to_bytes(&program.machine_code));
+
+    for steps in 1..=max_steps {
+        match simulator.step() {
+            StepOutcome::Running => {}
+            StepOutcome::Halted => return Ok(steps),
+            StepOutcome::InvalidInstruction { address } => {
+                return Err(format!("Invalid instruction at address {} after {} steps.", address, steps));
+            }
+        }
+    }
+
+    Err(format!("END was not reached within {} steps, stopped at address {}.", max_steps, simulator.program_counter()))
+}
+
+//Compares everything the assembler says about a source, the listing followed by its warnings or
+//the error it fails with, with a golden file.
+pub struct GoldenTest {
+    pub source: String,
+    pub golden_path: PathBuf,
+}
+
+impl GoldenTest {
+    pub fn new(source: &str, golden_path: &Path) -> Self {
+        GoldenTest { source: source.to_string(), golden_path: golden_path.to_path_buf() }
+    }
+
+    //Assembled with the default config.
+    pub fn output(&self) -> String {
+        let program =
+            match assemble_str(&self.source, &Config::default()) {
+                Ok(program) => program,
+                Err(e) => return format!("error: {}\n", e),
+            };
+
+        let mut listing = Vec::new();
+        ListingWriter { explain: false, profile: false }.write(&program, &mut listing).expect("Writing to memory does not fail.");
+
+        let mut output = String::from_utf8_lossy(&listing).into_owned();
+        for warning in &program.warnings {
+            output.push_str(&format!("warning: {}\n", warning));
+        }
+
+        output
+    }
+
+    //Fails with a diff of the lines that differ. With UPDATE_GOLDENS=1 the golden file is
+    //written instead.
+    pub fn run(&self) -> Result<(), String> {
+        let found = self.output();
+
+        if env::var(UPDATE_GOLDENS).is_ok_and(|value| value == "1") {
+            return fs::write(&self.golden_path, found)
+                .map_err(|e| format!("Unable to write {}: {}", self.golden_path.display(), e));
+        }
+
+        let expected = fs::read_to_string(&self.golden_path).map_err(|e| {
+            format!("Unable to read {}: {}, run with {}=1 to create it.", self.golden_path.display(), e, UPDATE_GOLDENS)
+        })?;
+
+        match expected == found {
+            true => Ok(()),
+            false => Err(format!("{} differs:\n{}", self.golden_path.display(), line_diff(&expected, &found))),
+        }
+    }
+}
+
+//Every line that differs, the expected line after - and the found line after +.
+fn line_diff(expected: &str, found: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let found: Vec<&str> = found.lines().collect();
+    let mut diff = String::new();
+
+    for i in 0..expected.len().max(found.len()) {
+        let expected_line = expected.get(i);
+        let found_line = found.get(i);
+        if expected_line == found_line {
+            continue;
+        }
+
+        diff.push_str(&format!("@@ line {}\n", i + 1));
+        if let Some(line) = expected_line {
+            diff.push_str(&format!("-{}\n", line));
+        }
+        if let Some(line) = found_line {
+            diff.push_str(&format!("+{}\n", line));
+        }
+    }
+
+    diff
+}
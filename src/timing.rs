@@ -0,0 +1,39 @@
+//Cycles taken by each instruction, loaded from a TOML file with --timing-model.
+//
+//    [cycles]
+//    ADD = 2
+//    DATA = 3
+//    JMP = 4
+//
+//Instructions that are not listed take one cycle, as they do without a timing model.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::config::ConfigError;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimingModel {
+    //Keyed by mnemonic, in order so the config hashed for --cache is the same on every run.
+    pub cycles: BTreeMap<String, usize>,
+}
+
+impl TimingModel {
+    pub fn load(path: &Path) -> Result<TimingModel, ConfigError> {
+        let content = fs::read_to_string(path).map_err(|error|
+            ConfigError::Io { path: path.to_path_buf(), error }
+        )?;
+
+        toml::from_str(&content).map_err(|error|
+            ConfigError::Parse { path: path.to_path_buf(), error }
+        )
+    }
+
+    pub fn cycles(&self, mnemonic: &str) -> usize {
+        self.cycles.get(mnemonic).copied().unwrap_or(1)
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+
+use crate::instructions::{truncation_description, FlagSet};
+
+//Categories of warnings that can be turned off individually.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WarnKind {
+    NoTerminal,
+    RedundantClf,
+    AlwaysFalseJif,
+    OverlappingPatch,
+    Checkpoint,
+    DataTruncated,
+    UnusedMark,
+    TooManyBreakpoints,
+    PcRelOffsetLarge,
+}
+
+impl WarnKind {
+    //Name used in # assembler: allow(...) comments.
+    pub fn name(&self) -> &'static str {
+        match self {
+            WarnKind::NoTerminal => "no_terminal",
+            WarnKind::RedundantClf => "redundant_clf",
+            WarnKind::AlwaysFalseJif => "always_false_jif",
+            WarnKind::OverlappingPatch => "overlapping_patch",
+            WarnKind::Checkpoint => "checkpoint",
+            WarnKind::DataTruncated => "data_truncated",
+            WarnKind::UnusedMark => "unused_mark",
+            WarnKind::TooManyBreakpoints => "too_many_breakpoints",
+            WarnKind::PcRelOffsetLarge => "pc_rel_offset_large",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            WarnKind::NoTerminal,
+            WarnKind::RedundantClf,
+            WarnKind::AlwaysFalseJif,
+            WarnKind::OverlappingPatch,
+            WarnKind::Checkpoint,
+            WarnKind::DataTruncated,
+            WarnKind::UnusedMark,
+            WarnKind::TooManyBreakpoints,
+            WarnKind::PcRelOffsetLarge,
+        ].into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+//A warning turned off for one place in the source with a # assembler: allow(kind) comment. It
+//covers its own line and the line after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Suppression {
+    pub kind: WarnKind,
+    pub line: usize,
+}
+
+impl Suppression {
+    //Every suppression in the text of a comment after its comment characters, such as
+    //"assembler: allow(unused_mark, redundant_clf)". Other comments and unknown warning names
+    //give none.
+    pub fn parse(comment: &str, line: usize) -> Vec<Suppression> {
+        let kinds = comment.trim()
+            .strip_prefix("assembler:")
+            .and_then(|rest| rest.trim().strip_prefix("allow("))
+            .and_then(|rest| rest.trim_end().strip_suffix(')'));
+
+        match kinds {
+            Some(kinds) => {
+                kinds.split(',')
+                    .filter_map(|name| WarnKind::from_name(name.trim()))
+                    .map(|kind| Suppression { kind, line })
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    pub fn covers(&self, warning: &Warning) -> bool {
+        self.kind == warning.kind() && (warning.line() == self.line || warning.line() == self.line + 1)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Warning {
+    NoTerminalInstruction { line: usize },
+    RedundantClearFlags { first_clf_line: usize, second_clf_line: usize },
+    AlwaysFalseConditional { line: usize, known_zero_flags: FlagSet },
+    OverlappingPatch { line: usize, address: usize, previous_line: usize },
+    CheckpointNotMet { line: usize, remaining: usize, required: usize },
+    DataTruncated { line: usize, column: usize, data: usize, truncated: usize, word_bits: usize },
+    UnusedMark { line: usize, mark: String },
+    //Line of the first breakpoint past the maximum.
+    TooManyBreakpoints { line: usize, maximum: usize },
+    //A PCREL whose mark is far enough away that moving code may put it out of range.
+    PcRelOffsetLarge { line: usize, mark: String, offset: i64 },
+}
+
+impl Warning {
+    pub fn kind(&self) -> WarnKind {
+        match self {
+            Warning::NoTerminalInstruction { .. } => WarnKind::NoTerminal,
+            Warning::RedundantClearFlags { .. } => WarnKind::RedundantClf,
+            Warning::AlwaysFalseConditional { .. } => WarnKind::AlwaysFalseJif,
+            Warning::OverlappingPatch { .. } => WarnKind::OverlappingPatch,
+            Warning::CheckpointNotMet { .. } => WarnKind::Checkpoint,
+            Warning::DataTruncated { .. } => WarnKind::DataTruncated,
+            Warning::UnusedMark { .. } => WarnKind::UnusedMark,
+            Warning::TooManyBreakpoints { .. } => WarnKind::TooManyBreakpoints,
+            Warning::PcRelOffsetLarge { .. } => WarnKind::PcRelOffsetLarge,
+        }
+    }
+
+    //Source line the warning is about, counting from 1.
+    pub fn line(&self) -> usize {
+        match self {
+            Warning::NoTerminalInstruction { line }
+            | Warning::AlwaysFalseConditional { line, .. }
+            | Warning::OverlappingPatch { line, .. }
+            | Warning::CheckpointNotMet { line, .. }
+            | Warning::DataTruncated { line, .. }
+            | Warning::UnusedMark { line, .. }
+            | Warning::TooManyBreakpoints { line, .. }
+            | Warning::PcRelOffsetLarge { line, .. } => *line,
+            Warning::RedundantClearFlags { second_clf_line, .. } => *second_clf_line,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::NoTerminalInstruction { line } => {
+                write!(
+                    f,
+                    "{} The last instruction is not END, JMP or JMPR, so execution only stops at the END appended by the assembler. Consider adding END at the bottom.",
+                    line,
+                )
+            }
+            Warning::RedundantClearFlags { first_clf_line, second_clf_line } => {
+                write!(
+                    f,
+                    "{} Redundant CLF, the previous CLF at line {} already cleared all flags.",
+                    second_clf_line, first_clf_line,
+                )
+            }
+            Warning::AlwaysFalseConditional { line, known_zero_flags } => {
+                write!(
+                    f,
+                    "{} JIF can never jump, the flags {} are still clear from the last CLF.",
+                    line, known_zero_flags,
+                )
+            }
+            Warning::OverlappingPatch { line, address, previous_line } => {
+                write!(
+                    f,
+                    "{} Address {} was already patched at line {}, the earlier value is overwritten.",
+                    line, address, previous_line,
+                )
+            }
+            Warning::CheckpointNotMet { line, remaining, required } => {
+                write!(
+                    f,
+                    "{} Only {} RAM cells are left at this checkpoint, {} were expected.",
+                    line, remaining, required,
+                )
+            }
+            Warning::DataTruncated { line, column, data, truncated, word_bits } => {
+                write!(f, "{}:{} {}", line, column, truncation_description(*data, *truncated, *word_bits))
+            }
+            Warning::UnusedMark { line, mark } => {
+                write!(f, "{} Mark {} is never jumped to.", line, mark)
+            }
+            Warning::PcRelOffsetLarge { line, mark, offset } => {
+                write!(f, "{} Mark {} is {} RAM cells away, close to the most a PCREL can reach.", line, mark, offset)
+            }
+            Warning::TooManyBreakpoints { line, maximum } => {
+                write!(f, "{} More than {} breakpoints are used, a debugger may not stop at this one or the ones after it.", line, maximum)
+            }
+        }
+    }
+}
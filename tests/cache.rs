@@ -0,0 +1,37 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+//Assembles the input in dir with --cache, returning whether assembly was skipped.
+fn assemble_cached(dir: &Path, input: &str) -> bool {
+    let output = Command::new(env!("CARGO_BIN_EXE_logical_cpu_assembler"))
+        .current_dir(dir)
+        .env("RUST_LOG", "info")
+        .arg(input)
+        .arg("-o").arg(Path::new(input).with_extension("ms"))
+        .args(["--cache", "--cache-dir", "."])
+        .output()
+        .expect("Unable to run the assembler.");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    String::from_utf8_lossy(&output.stderr).contains("is unchanged, skipping assembly.")
+}
+
+#[test]
+fn inputs_in_one_directory_keep_their_own_entries() {
+    let dir = std::env::temp_dir().join(format!("cache_inputs_{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Unable to create the directory.");
+    fs::write(dir.join("a.asm"), "DATA R0 1\nEND\n").expect("Unable to write a.asm.");
+    fs::write(dir.join("b.asm"), "DATA R0 2\nEND\n").expect("Unable to write b.asm.");
+
+    assert!(!assemble_cached(&dir, "a.asm"));
+    assert!(!assemble_cached(&dir, "b.asm"));
+    assert!(assemble_cached(&dir, "a.asm"));
+    assert!(assemble_cached(&dir, "b.asm"));
+
+    fs::write(dir.join("a.asm"), "DATA R0 3\nEND\n").expect("Unable to write a.asm.");
+    assert!(!assemble_cached(&dir, "a.asm"));
+    assert!(assemble_cached(&dir, "b.asm"));
+
+    fs::remove_dir_all(&dir).ok();
+}
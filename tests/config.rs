@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Arc;
+
+use logical_cpu_assembler::assembler::Assembler;
+use logical_cpu_assembler::config::{Config, CONFIG_FILE_NAME, ENV_ADDRESS_BITS, ENV_FORMAT, ENV_PREFIX};
+use logical_cpu_assembler::file_system::MockFileSystem;
+use logical_cpu_assembler::{assemble_str, AssemblerError};
+
+#[test]
+fn library_rejects_an_invalid_config() {
+    let config = Config { address_bits: 64, ..Config::default() };
+
+    match assemble_str("DATA R0 5\nEND\n", &config) {
+        Err(error @ AssemblerError::InvalidConfig { .. }) => {
+            assert_eq!(error.code(), "E034");
+            assert!(error.to_string().contains("address bits must be from"), "{}", error);
+        }
+        other => panic!("Expected an invalid config, found {:?}.", other.map(|program| program.machine_code)),
+    }
+}
+
+#[test]
+fn assembler_rejects_an_invalid_config() {
+    let config = Config { address_bits: 64, word_bits: 2, ..Config::default() };
+
+    let errors: Vec<String> = Assembler::new(Arc::new(MockFileSystem::new()), config).err()
+        .expect("The config is invalid.")
+        .iter()
+        .map(|error| error.to_string())
+        .collect();
+
+    //Every problem is found, not only the first.
+    assert!(errors.contains(&"Invalid options, address bits must be from 4 to 16, found 64.".to_string()), "{:?}", errors);
+    assert!(errors.contains(&"Invalid options, word bits must be from 4 to 32, found 2.".to_string()), "{:?}", errors);
+}
+
+//Runs the assembler in a directory of its own holding program.asm and, when given, an
+//.assembler.toml, with the LCPU_ variables of this process cleared.
+fn run_in_dir(name: &str, config_file: Option<&str>, env: &[(&str, &str)], args: &[&str]) -> (Output, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("config_{}_{}", name, std::process::id()));
+    fs::create_dir_all(&dir).expect("Unable to create the directory.");
+    fs::write(dir.join("program.asm"), "DATA R0 5\nEND\n").expect("Unable to write the program.");
+    if let Some(config_file) = config_file {
+        fs::write(dir.join(CONFIG_FILE_NAME), config_file).expect("Unable to write the config file.");
+    }
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_logical_cpu_assembler"));
+    command.current_dir(&dir).arg("program.asm").args(args);
+    for (name, _) in std::env::vars().filter(|(name, _)| name.starts_with(ENV_PREFIX)) {
+        command.env_remove(name);
+    }
+    command.envs(env.iter().copied());
+
+    (command.output().expect("Unable to run the assembler."), dir)
+}
+
+fn read_output(dir: &Path) -> String {
+    let output = fs::read_to_string(dir.join("program.out")).expect("Unable to read the output.");
+    fs::remove_dir_all(dir).ok();
+    output
+}
+
+const INTEL_HEX: &str = ":040000002005CFCF39\n:00000001FF\n";
+const MS: &str = "00100000\n00000101\n11001111\n11001111\n";
+
+#[test]
+fn config_file_format_selects_intel_hex() {
+    let (output, dir) = run_in_dir("file_format", Some("format = \"ihex\"\n"), &[], &["-o", "program.out"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(read_output(&dir), INTEL_HEX);
+}
+
+#[test]
+fn env_overrides_the_config_file() {
+    let (output, dir) = run_in_dir("env_over_file", Some("format = \"ms\"\n"), &[(ENV_FORMAT, "ihex")], &["-o", "program.out"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(read_output(&dir), INTEL_HEX);
+}
+
+#[test]
+fn command_line_overrides_the_env() {
+    let (output, dir) = run_in_dir("cli_over_env", Some("format = \"ihex\"\n"), &[(ENV_FORMAT, "ihex")], &["--format", "ms", "-o", "program.out"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(read_output(&dir), MS);
+}
+
+#[test]
+fn malformed_env_value_is_an_error() {
+    let (output, dir) = run_in_dir("malformed_env", None, &[(ENV_ADDRESS_BITS, "eight")], &["-o", "program.out"]);
+    fs::remove_dir_all(&dir).ok();
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("Invalid value eight for environment variable LCPU_ADDRESS_BITS."),
+        "{}", String::from_utf8_lossy(&output.stderr),
+    );
+}
+
+#[test]
+fn builder_fails_with_the_first_problem() {
+    let error = Config::builder().address_bits(2).word_bits(2).build().expect_err("The config is invalid.");
+
+    assert_eq!(error.to_string(), "Invalid options, address bits must be from 4 to 16, found 2.");
+}
@@ -0,0 +1,63 @@
+use logical_cpu_assembler::editor::rename_mark;
+use logical_cpu_assembler::AssemblerError;
+
+const SOURCE: &str = "\
+.entry start
+MARK start
+DATA R0 1
+JMP start
+JIF C start
+JIFN Z start
+PCREL R1 start
+JMP start+2
+MARK other
+JMP other
+END
+";
+
+#[test]
+fn rename_mark_renames_every_use() {
+    let renamed = rename_mark(SOURCE, "start", "begin").expect("The mark is renamed.");
+
+    assert_eq!(renamed, "\
+.entry begin
+MARK begin
+DATA R0 1
+JMP begin
+JIF C begin
+JIFN Z begin
+PCREL R1 begin
+JMP begin+2
+MARK other
+JMP other
+END
+");
+}
+
+//Only the uses mark_tokens knows of are renamed, a mark in an .assert expression is left as it is.
+#[test]
+fn rename_mark_leaves_assert_expressions() {
+    let renamed = rename_mark("MARK start\n.assert start == 0\nJMP start\nEND\n", "start", "begin").expect("The mark is renamed.");
+
+    assert_eq!(renamed, "MARK begin\n.assert start == 0\nJMP begin\nEND\n");
+}
+
+#[test]
+fn rename_mark_to_an_invalid_name() {
+    for name in ["R0", "12", ".start", "__internal_start", "a b"] {
+        assert!(
+            matches!(rename_mark(SOURCE, "start", name), Err(AssemblerError::InvalidMarkName { .. })),
+            "{} should not be a valid mark name", name,
+        );
+    }
+}
+
+#[test]
+fn rename_mark_that_is_not_defined() {
+    assert!(matches!(rename_mark(SOURCE, "missing", "begin"), Err(AssemblerError::MarkNotFound { mark }) if mark == "missing"));
+}
+
+#[test]
+fn rename_mark_to_a_name_in_use() {
+    assert!(matches!(rename_mark(SOURCE, "start", "other"), Err(AssemblerError::MarkAlreadyDefined { mark }) if mark == "other"));
+}
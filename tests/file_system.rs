@@ -0,0 +1,49 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use logical_cpu_assembler::assembler::Assembler;
+use logical_cpu_assembler::config::Config;
+use logical_cpu_assembler::file_system::{FileSystem, MockFileSystem};
+use logical_cpu_assembler::output::{atomic_write, OutputFormat, OutputTarget};
+
+#[test]
+fn assembles_in_memory() {
+    let file_system = Arc::new(MockFileSystem::new().with_file("program.asm", "DATA R0 5\nEND\n"));
+    let assembler = Assembler::new(file_system.clone(), Config::default()).expect("The config is valid.");
+
+    let program = assembler.assemble_file(Path::new("program.asm")).expect("The program assembles.");
+    let target = OutputTarget { format: OutputFormat::Ms, path: PathBuf::from("program.ms"), coe_radix: None };
+    assembler.write_output(&program, &target).expect("The output is written.");
+
+    assert_eq!(file_system.contents(Path::new("program.ms")).as_deref(), Some(b"00100000\n00000101\n11001111\n11001111\n".as_slice()));
+    assert!(!Path::new("program.ms").exists());
+}
+
+#[test]
+fn missing_file() {
+    let file_system = Arc::new(MockFileSystem::new());
+    assert!(!file_system.exists(Path::new("missing.asm")));
+
+    let assembler = Assembler::new(file_system, Config::default()).expect("The config is valid.");
+    assert!(assembler.assemble_file(Path::new("missing.asm")).is_err());
+}
+
+#[test]
+fn failed_atomic_write_keeps_the_old_file() {
+    let path = std::env::temp_dir().join(format!("atomic_write_{}.ms", std::process::id()));
+    std::fs::write(&path, "old\n").unwrap();
+
+    let result = atomic_write(&path, |file| {
+        file.write_all(b"new\n")?;
+        Err(io::Error::other("interrupted"))
+    });
+
+    assert!(result.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "old\n");
+    assert!(!path.with_extension("ms.tmp").exists());
+
+    atomic_write(&path, |file| file.write_all(b"new\n")).expect("The file is written.");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "new\n");
+    std::fs::remove_file(&path).ok();
+}
@@ -0,0 +1,28 @@
+use std::fs;
+use std::path::Path;
+
+use logical_cpu_assembler::test_harness::{run_fixture, TestResult};
+
+#[test]
+fn fixtures() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+
+    let mut paths: Vec<_> = fs::read_dir(&dir).expect("Unable to read the tests directory.")
+        .map(|entry| entry.expect("Unable to read the tests directory.").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "asmtest"))
+        .collect();
+    paths.sort();
+
+    assert!(!paths.is_empty(), "No .asmtest files found in {}.", dir.display());
+
+    let failures: Vec<String> = paths.iter()
+        .filter_map(|path| {
+            match run_fixture(path) {
+                TestResult::Passed => None,
+                result => Some(format!("{}: {}", path.display(), result)),
+            }
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
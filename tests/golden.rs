@@ -0,0 +1,27 @@
+use std::fs;
+use std::path::Path;
+
+use logical_cpu_assembler::test_harness::GoldenTest;
+
+//Every .asm file in tests/golden is compared with the .golden file next to it.
+#[test]
+fn golden() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+
+    let mut paths: Vec<_> = fs::read_dir(&dir).expect("Unable to read the golden directory.")
+        .map(|entry| entry.expect("Unable to read the golden directory.").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "asm"))
+        .collect();
+    paths.sort();
+
+    assert!(!paths.is_empty(), "No .asm files found in {}.", dir.display());
+
+    let failures: Vec<String> = paths.iter()
+        .filter_map(|path| {
+            let source = fs::read_to_string(path).expect("Unable to read a golden source.");
+            GoldenTest::new(&source, &path.with_extension("golden")).run().err()
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,130 @@
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+DATA R0 1
+END
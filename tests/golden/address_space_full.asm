@@ -0,0 +1,255 @@
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+END
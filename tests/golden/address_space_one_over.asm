@@ -0,0 +1,256 @@
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+ADD R0 R1
+END
@@ -0,0 +1,8 @@
+DATA R0 12
+DATA R1 10
+ADD R0 R1
+AND R0 R1
+OR R0 R1
+XOR R0 R1
+NOT R0 R1
+END
@@ -0,0 +1,9 @@
+#Right after a CLF every flag is zero, so the first JIF never jumps. After the ADD the flags
+#are not known any more.
+MARK top
+DATA R0 1
+CLF
+JIF CZ top
+ADD R0 R1
+JIF CZ top
+END
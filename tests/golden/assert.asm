@@ -0,0 +1,9 @@
+MARK BUFFER_START
+NOP
+NOP
+MARK BUFFER_END
+.assert BUFFER_END - BUFFER_START == 2 "buffer must be exactly 2 cells"
+.assert (BUFFER_END <= 0xFF) && !(1 > 2)
+.assert LATER == 2 "later is at 4"
+MARK LATER
+END
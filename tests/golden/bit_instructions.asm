@@ -0,0 +1,4 @@
+DATA R0 0
+BSET R0 2
+BCLR R0 2
+END
@@ -0,0 +1,7 @@
+# A program that is mostly comments.
+
+# DATA R0 1
+DATA R1 2
+
+# END is still needed.
+END
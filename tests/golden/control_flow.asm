@@ -0,0 +1,13 @@
+DATA R0 5
+DATA R1 1
+.while Z loop
+CLF
+NOT R1 R2
+AND R0 R2
+.if E
+DATA R3 1
+.endif
+CLF
+ADD R2 R0
+.endwhile loop
+END
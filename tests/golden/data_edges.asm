@@ -0,0 +1,5 @@
+DATA R0 0
+DATA R1 127
+DATA R2 128
+DATA R3 255
+END
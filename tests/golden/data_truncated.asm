@@ -0,0 +1,3 @@
+DATA R0 256
+DATA R1 1023
+END
@@ -0,0 +1,6 @@
+# DBG writes the debug opcode with the register in its low bits.
+DATA R2 7
+DBG R2
+ADD R2 R2
+DBG R0
+END
@@ -0,0 +1,6 @@
+.entry main
+MARK helper
+DATA R0 1
+JMPR R0
+MARK main
+JMP helper
@@ -0,0 +1,8 @@
+MARK start
+DATA R0 1
+JMP start
+.module boot
+.entry start
+MARK start
+DATA R1 2
+JMP start
@@ -0,0 +1,10 @@
+DATA R0 1
+.feature LOG
+DATA R1 2
+.feature VERBOSE
+.endm
+MARK unused
+.endfeature
+JMP missing
+.endfeature
+END
@@ -0,0 +1,4 @@
+.flagset OVERFLOW CA
+MARK top
+JIF OVERFLOW top
+END
@@ -0,0 +1,4 @@
+#The complement of every flag is a JIF of no flags, which never jumps.
+MARK top
+DATA R0 1
+JIFN CAEZ top
@@ -0,0 +1,3 @@
+MARK loop
+DATA R0 1
+JMP loop
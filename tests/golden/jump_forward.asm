@@ -0,0 +1,4 @@
+JMP skip
+DATA R0 1
+MARK skip
+END
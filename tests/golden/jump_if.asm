@@ -0,0 +1,8 @@
+MARK top
+CLF
+DATA R0 1
+DATA R1 2
+ADD R0 R1
+JIF C top
+JIF AEZ top
+END
@@ -0,0 +1,4 @@
+MARK top
+JIFN C top
+JIFN CA top
+END
@@ -0,0 +1,3 @@
+DATA R0 4
+JMPR R0
+END
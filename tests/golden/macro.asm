@@ -0,0 +1,7 @@
+.macro DOUBLE reg
+SHL \reg \reg
+.endm
+DATA R0 3
+DOUBLE R0
+DOUBLE R0
+END
@@ -0,0 +1,7 @@
+MARK START
+DATA R0 1
+MARK LOOP
+ADD R0 R1
+JMP LOOP+1
+JIF E START-0
+JMP LOOP-2
@@ -0,0 +1,5 @@
+DATA R0 100
+DATA R1 42
+ST R0 R1
+LD R0 R2
+END
@@ -0,0 +1,2 @@
+JMP nowhere
+END
@@ -0,0 +1,4 @@
+.mnemonic PLUS ADD
+DATA R0 1
+PLUS R0 R0
+END
@@ -0,0 +1,13 @@
+MARK start
+JMP math.multiply
+.module math
+MARK multiply
+DATA R0 1
+JIF Z done
+JMP multiply
+MARK done
+JMP start
+.module io
+MARK print
+JMP math.done
+JMP print
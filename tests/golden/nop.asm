@@ -0,0 +1,3 @@
+NOP
+NOP
+END
@@ -0,0 +1,6 @@
+MARK back
+DATA R1 1
+PCREL R0 back
+PCREL R2 ahead
+MARK ahead
+END
@@ -0,0 +1,6 @@
+DATA R0 6
+DATA R1 3
+NAND R0 R1
+NOR R0 R1
+XNOR R0 R1
+END
@@ -0,0 +1,7 @@
+#The second CLF has nothing to clear, the one after the ADD does.
+DATA R0 1
+CLF
+CLF
+ADD R0 R1
+CLF
+END
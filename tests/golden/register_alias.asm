@@ -0,0 +1,6 @@
+.alias counter R0
+.alias step R1
+MARK counter
+DATA step 1
+ADD step counter
+JIF Z counter
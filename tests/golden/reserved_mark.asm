@@ -0,0 +1,4 @@
+#Marks starting with __internal_ are kept for the marks the assembler makes.
+DATA R0 1
+MARK __internal_loop
+JMP __internal_loop
@@ -0,0 +1,5 @@
+DATA R0 1
+SHL R0 R0
+SHL R0 R0
+SHR R0 R1
+END
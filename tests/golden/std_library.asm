@@ -0,0 +1,5 @@
+.include <std>
+DATA R0 1
+DATA R1 2
+SWAP R0 R1
+END
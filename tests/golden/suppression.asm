@@ -0,0 +1,6 @@
+#A suppression covers the line after it, the mark after that one is still warned about.
+# assembler: allow(unused_mark)
+MARK unused_suppressed
+MARK unused_warned
+DATA R0 1
+END
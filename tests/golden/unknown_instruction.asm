@@ -0,0 +1,3 @@
+DATA R0 1
+FOO R0 R1
+END
@@ -0,0 +1,38 @@
+use std::fs;
+use std::path::PathBuf;
+
+use logical_cpu_assembler::assemble_str;
+use logical_cpu_assembler::config::{Config, ConfigError};
+use logical_cpu_assembler::isa::Isa;
+
+fn write_isa(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("isa_{}_{}.toml", name, std::process::id()));
+    fs::write(&path, content).expect("Unable to write the instruction set.");
+    path
+}
+
+#[test]
+fn custom_instructions_fill_the_word() {
+    let path = write_isa("fill", "[[instructions]]\nmnemonic = \"MOV\"\nopcode = \"0111\"\noperands = [\"REG\", \"REG\"]\n");
+    let isa = Isa::load(&path, 16).expect("The instruction set loads.");
+    fs::remove_file(&path).ok();
+
+    let config = Config { word_bits: 16, isa: Some(isa), ..Config::default() };
+    let program = assemble_str("MOV R1 R2\n", &config).expect("The program assembles.");
+
+    assert_eq!(program.machine_code[0], "0111011000000000");
+}
+
+#[test]
+fn instruction_wider_than_the_word() {
+    let path = write_isa("wide", "[[instructions]]\nmnemonic = \"CMP\"\nopcode = \"111100\"\noperands = [\"REG\", \"REG\"]\n");
+    let result = Isa::load(&path, 8);
+    fs::remove_file(&path).ok();
+
+    match result {
+        Err(error @ ConfigError::InvalidIsa { .. }) => {
+            assert!(error.to_string().contains("CMP takes 10 bits, more than the 8 bits of a RAM cell"), "{}", error);
+        }
+        other => panic!("Expected an invalid instruction set, found {:?}.", other),
+    }
+}
@@ -0,0 +1,76 @@
+use logical_cpu_assembler::config::Config;
+use logical_cpu_assembler::output::coe::{CoeRadix, CoeWriter};
+use logical_cpu_assembler::output::OutputWriter;
+use logical_cpu_assembler::{assemble_str, AssembledProgram};
+
+//Eight instructions, one of each kind of RAM cell layout.
+const PROGRAM: &str = "\
+DATA R0 5
+DATA R1 3
+CLF
+ADD R0 R1
+MARK loop
+JIF Z loop
+XOR R2 R2
+JMP loop
+END
+";
+
+fn write(writer: &dyn OutputWriter, program: &AssembledProgram) -> String {
+    let mut out = Vec::new();
+    writer.write(program, &mut out).expect("Writing to memory does not fail.");
+    String::from_utf8(out).expect("The output is text.")
+}
+
+#[test]
+fn coe_of_an_eight_instruction_program() {
+    let program = assemble_str(PROGRAM, &Config::default()).expect("The program assembles.");
+
+    assert_eq!(write(&CoeWriter { radix: CoeRadix::Binary }, &program), "\
+memory_initialization_radix=2;
+memory_initialization_vector=
+00100000,
+00000101,
+00100001,
+00000011,
+01100000,
+10000001,
+01010001,
+00000110,
+11101010,
+01000000,
+00000110,
+11001111,
+11001111;
+");
+
+    assert_eq!(write(&CoeWriter { radix: CoeRadix::Hex }, &program), "\
+memory_initialization_radix=16;
+memory_initialization_vector=
+20,
+05,
+21,
+03,
+60,
+81,
+51,
+06,
+EA,
+40,
+06,
+CF,
+CF;
+");
+}
+
+#[test]
+fn memory_image_starts_at_the_base_address() {
+    let config = Config { base_address: 4, ..Config::default() };
+    let program = assemble_str("DATA R0 1\nEND\n", &config).expect("The program assembles.");
+
+    assert_eq!(
+        program.to_memory_image(12, 0xAA, 0xFF),
+        vec![0xAA, 0xAA, 0xAA, 0xAA, 0x20, 0x01, 0xCF, 0xCF, 0xFF, 0xFF, 0xFF, 0xFF],
+    );
+    assert_eq!(program.to_memory_image(6, 0xAA, 0xFF), vec![0xAA, 0xAA, 0xAA, 0xAA, 0x20, 0x01]);
+}
@@ -0,0 +1,23 @@
+use std::fs;
+use std::process::Command;
+
+//The assembler exits early on an error, the trace is still written out in full.
+#[test]
+fn profile_is_written_when_assembly_fails() {
+    let dir = std::env::temp_dir().join(format!("profile_error_{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Unable to create the directory.");
+    fs::write(dir.join("program.asm"), "JMP nowhere\nEND\n").expect("Unable to write the program.");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_logical_cpu_assembler"))
+        .current_dir(&dir)
+        .args(["program.asm", "-o", "program.ms", "--profile", "trace.json"])
+        .output()
+        .expect("Unable to run the assembler.");
+    assert!(!output.status.success());
+
+    let trace = fs::read_to_string(dir.join("trace.json")).expect("Unable to read the trace.");
+    assert!(trace.trim_end().ends_with(']'), "{}", trace);
+    assert!(trace.contains("parse_statements"), "{}", trace);
+
+    fs::remove_dir_all(&dir).ok();
+}
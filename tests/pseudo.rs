@@ -0,0 +1,202 @@
+use logical_cpu_assembler::config::Config;
+use logical_cpu_assembler::instructions::Register;
+use logical_cpu_assembler::output::to_bytes;
+use logical_cpu_assembler::test_harness::run_program;
+use logical_cpu_assembler::{assemble_str, CpuFlag, SimulatorInterface, StepOutcome};
+
+const END: u8 = 0b11001111;
+
+//Runs the instructions of the CPU on 256 RAM cells, enough to check what the pseudo instructions
+//expand to.
+struct Cpu {
+    ram: [u8; 256],
+    registers: [u8; 4],
+    carry: bool,
+    a_larger: bool,
+    equal: bool,
+    zero: bool,
+    program_counter: u8,
+}
+
+impl Cpu {
+    fn new() -> Self {
+        Cpu { ram: [0; 256], registers: [0; 4], carry: false, a_larger: false, equal: false, zero: false, program_counter: 0 }
+    }
+
+    //The ALU sets every flag, only ADD and the shifts can carry.
+    fn alu(&mut self, operation: u8, reg_a: usize, reg_b: usize) {
+        let (a, b) = (self.registers[reg_a], self.registers[reg_b]);
+
+        let (result, carry) =
+            match operation {
+                0b000 => a.overflowing_add(b),
+                0b001 => (a >> 1, a & 1 == 1),
+                0b010 => (a << 1, a >> 7 == 1),
+                0b011 => (!a, false),
+                0b100 => (a & b, false),
+                0b101 => (a | b, false),
+                _ => (a ^ b, false),
+            };
+
+        self.registers[reg_b] = result;
+        self.carry = carry;
+        self.a_larger = a > b;
+        self.equal = a == b;
+        self.zero = result == 0;
+    }
+}
+
+impl SimulatorInterface for Cpu {
+    fn load_binary(&mut self, binary: &[u8]) {
+        *self = Cpu::new();
+        self.ram[..binary.len()].copy_from_slice(binary);
+    }
+
+    fn get_register(&self, reg: Register) -> u8 {
+        self.registers[Register::index(reg)]
+    }
+
+    fn get_flag(&self, flag: CpuFlag) -> bool {
+        match flag {
+            CpuFlag::Carry => self.carry,
+            CpuFlag::ALarger => self.a_larger,
+            CpuFlag::Equal => self.equal,
+            CpuFlag::Zero => self.zero,
+        }
+    }
+
+    fn step(&mut self) -> StepOutcome {
+        let address = self.program_counter;
+        let instruction = self.ram[address as usize];
+        let operand = self.ram[address.wrapping_add(1) as usize];
+        let (reg_a, reg_b) = ((instruction >> 2 & 0b11) as usize, (instruction & 0b11) as usize);
+        self.program_counter = address.wrapping_add(1);
+
+        if instruction == END {
+            return StepOutcome::Halted;
+        }
+
+        match (instruction >> 4, instruction & 0b1111) {
+            (0b1000..=0b1110, _) => self.alu(instruction >> 4 & 0b111, reg_a, reg_b),
+            (0b0000, _) => self.registers[reg_b] = self.ram[self.registers[reg_a] as usize],
+            (0b0001, _) => self.ram[self.registers[reg_b] as usize] = self.registers[reg_a],
+            (0b0010, 0b0000..=0b0011) => {
+                self.registers[reg_b] = operand;
+                self.program_counter = address.wrapping_add(2);
+            }
+            (0b0011, 0b0000..=0b0011) => self.program_counter = self.registers[reg_b],
+            (0b0100, 0b0000) => self.program_counter = operand,
+            (0b0101, flags) => {
+                let set = [self.carry, self.a_larger, self.equal, self.zero].iter()
+                    .zip([0b1000, 0b0100, 0b0010, 0b0001])
+                    .any(|(value, bit)| *value && flags & bit != 0);
+
+                self.program_counter = if set { operand } else { address.wrapping_add(2) };
+            }
+            (0b0110, 0b0000) => {
+                self.carry = false;
+                self.a_larger = false;
+                self.equal = false;
+                self.zero = false;
+            }
+            _ => return StepOutcome::InvalidInstruction { address },
+        }
+
+        StepOutcome::Running
+    }
+
+    fn program_counter(&self) -> u8 {
+        self.program_counter
+    }
+}
+
+//Assembles DATA R0 a, DATA R1 b and the instruction once, then runs it for each pair of values by
+//writing them over the DATA cells. Returns R1 after each run.
+fn run_with_operands(instruction: &str, operands: impl Iterator<Item = (u8, u8)>) -> Vec<((u8, u8), u8)> {
+    let program = assemble_str(&format!("DATA R0 0\nDATA R1 0\n{}\nEND\n", instruction), &Config::default())
+        .expect("The program assembles.");
+    let binary = to_bytes(&program.machine_code);
+    let mut cpu = Cpu::new();
+
+    operands
+        .map(|(a, b)| {
+            let mut binary = binary.clone();
+            binary[1] = a;
+            binary[3] = b;
+
+            cpu.load_binary(&binary);
+            for _ in 0..64 {
+                match cpu.step() {
+                    StepOutcome::Running => {}
+                    StepOutcome::Halted => return ((a, b), cpu.get_register(Register::R1)),
+                    StepOutcome::InvalidInstruction { address } => panic!("Invalid instruction at address {}.", address),
+                }
+            }
+
+            panic!("{} of {} and {} did not reach END.", instruction, a, b);
+        })
+        .collect()
+}
+
+fn every_pair() -> impl Iterator<Item = (u8, u8)> {
+    (0..=255).flat_map(|a| (0..=255).map(move |b| (a, b)))
+}
+
+#[test]
+fn xnor_of_every_pair_of_bytes() {
+    for ((a, b), result) in run_with_operands("XNOR R0 R1", every_pair()) {
+        assert_eq!(result, !(a ^ b), "XNOR of {:#010b} and {:#010b}", a, b);
+    }
+}
+
+#[test]
+fn rotate_left_of_every_byte() {
+    for ((a, _), result) in run_with_operands("ROL R0 R1", (0..=255).map(|a| (a, 0))) {
+        assert_eq!(result, a.rotate_left(1), "ROL of {:#010b}", a);
+    }
+}
+
+#[test]
+fn rotate_right_of_every_byte() {
+    for ((a, _), result) in run_with_operands("ROR R0 R1", (0..=255).map(|a| (a, 0))) {
+        assert_eq!(result, a.rotate_right(1), "ROR of {:#010b}", a);
+    }
+}
+
+#[test]
+fn run_program_reaches_end() {
+    let program = assemble_str("DATA R2 7\nROL R2 R1\nEND\n", &Config::default()).expect("The program assembles.");
+    let mut cpu = Cpu::new();
+
+    let steps = run_program(&mut cpu, &program, 64).expect("The program reaches END.");
+
+    assert_eq!(cpu.get_register(Register::R1), 14);
+    assert!(steps > 1);
+}
+
+//The four combinations of 1 bit inputs, as the lowest bit of each byte, with the lowest bit of the
+//answer.
+fn truth_table(instruction: &str) -> Vec<(u8, u8, u8)> {
+    run_with_operands(instruction, [(0, 0), (1, 0), (0, 1), (1, 1)].into_iter())
+        .into_iter()
+        .map(|((a, b), result)| (a, b, result & 1))
+        .collect()
+}
+
+#[test]
+fn nand_truth_table() {
+    assert_eq!(truth_table("NAND R0 R1"), vec![(0, 0, 1), (1, 0, 1), (0, 1, 1), (1, 1, 0)]);
+
+    for ((a, b), result) in run_with_operands("NAND R0 R1", every_pair()) {
+        assert_eq!(result, !(a & b), "NAND of {:#010b} and {:#010b}", a, b);
+    }
+}
+
+#[test]
+fn nor_truth_table() {
+    assert_eq!(truth_table("NOR R0 R1"), vec![(0, 0, 1), (1, 0, 0), (0, 1, 0), (1, 1, 0)]);
+
+    for ((a, b), result) in run_with_operands("NOR R0 R1", every_pair()) {
+        assert_eq!(result, !(a | b), "NOR of {:#010b} and {:#010b}", a, b);
+    }
+}
@@ -0,0 +1,40 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
+
+//Assembles the program in its own process and returns the hash of every file written.
+fn assemble(source: &Path, dir: &Path) -> Vec<[u8; 32]> {
+    fs::create_dir_all(dir).expect("Unable to create the output directory.");
+
+    let outputs: Vec<PathBuf> = ["program.bin", "program.sym", "program.xref", "program.map.json"].iter()
+        .map(|name| dir.join(name))
+        .collect();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_logical_cpu_assembler"))
+        .arg(source)
+        .args(["--format", "binary-v2", "-o"]).arg(&outputs[0])
+        .arg("--symbols").arg(&outputs[1])
+        .arg("--xref").arg(&outputs[2])
+        .arg("--dap-source-map").arg(&outputs[3])
+        .status()
+        .expect("Unable to run the assembler.");
+    assert!(status.success(), "Assembling {} failed.", source.display());
+
+    outputs.iter()
+        .map(|path| Sha256::digest(fs::read(path).expect("Unable to read an output.")).into())
+        .collect()
+}
+
+#[test]
+fn output_is_reproducible() {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("programs").join("multiplication");
+    let dir = std::env::temp_dir().join(format!("logical_cpu_assembler_reproducible_{}", std::process::id()));
+
+    let first = assemble(&source, &dir.join("first"));
+    let second = assemble(&source, &dir.join("second"));
+    fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(first, second);
+}